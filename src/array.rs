@@ -0,0 +1,196 @@
+use core::ops::{Deref, Index, IndexMut};
+use core::slice::SliceIndex;
+
+use crate::iter::{AdvancingLap, Lap};
+use crate::wrap;
+
+pub(crate) struct NonEmptyArray<const N: usize>;
+
+impl<const N: usize> NonEmptyArray<N> {
+    // Referencing this constant fails to compile when N is zero.
+    pub(crate) const CHECK: () = assert!(N > 0, "a CircularVec must contain at least one item");
+}
+
+/// A CircularVec backed by an array of `N` items instead of a Vec.
+///
+/// It lives entirely on the stack and does not need an allocator, and
+/// its constructors are `const fn`, so it can be built at compile time:
+///
+///     # use circular_vec::CircularArray;
+///     const COLOURS: CircularArray<&str, 3> = CircularArray::new(["red", "green", "blue"]);
+///
+///     let mut colours = COLOURS;
+///     assert_eq!(colours.next(), &"red");
+///     colours.skip(5);
+///     assert_eq!(colours.next(), &"red");
+///
+/// Using an empty array (`N == 0`) is rejected at compile time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CircularArray<T, const N: usize> {
+    items: [T; N],
+    index: usize,
+}
+
+impl<T, const N: usize> CircularArray<T, N> {
+    /// Create a CircularArray whose cursor starts at the first item.
+    pub const fn new(items: [T; N]) -> Self {
+        Self::starting_at(items, 0)
+    }
+
+    /// Create a CircularArray whose cursor starts at `index`, wrapping
+    /// around if it is past the end.
+    pub const fn starting_at(items: [T; N], index: usize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = NonEmptyArray::<N>::CHECK;
+        CircularArray {
+            items,
+            index: index % N,
+        }
+    }
+
+    /// Get an immutable reference to the next item in the CircularArray.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &T {
+        let original_index = self.index;
+        self.index = wrap::increment(self.index, N);
+        &self.items[original_index]
+    }
+
+    /// Get a mutable reference to the next item in the CircularArray.
+    pub fn next_mut(&mut self) -> &mut T {
+        let original_index = self.index;
+        self.index = wrap::increment(self.index, N);
+        &mut self.items[original_index]
+    }
+
+    /// Step the cursor backwards and get an immutable reference to the
+    /// item it now points at. This is the inverse of `next`.
+    pub fn prev(&mut self) -> &T {
+        self.index = wrap::decrement(self.index, N);
+        &self.items[self.index]
+    }
+
+    /// Move past the next `n` items without returning them.
+    /// This takes constant time regardless of `n`.
+    pub fn skip(&mut self, n: usize) {
+        self.index = wrap::forward(self.index, n, N);
+    }
+
+    /// Move the cursor back by `n` items.
+    /// This takes constant time regardless of `n`.
+    pub fn rewind(&mut self, n: usize) {
+        self.index = wrap::backward(self.index, n, N);
+    }
+
+    /// Get an immutable reference to the item `next` would return,
+    /// without moving the cursor.
+    pub fn peek(&self) -> &T {
+        &self.items[self.index]
+    }
+
+    /// The index of the item `next` would return.
+    pub const fn position(&self) -> usize {
+        self.index
+    }
+
+    /// Iterate over every item exactly once, starting with the item `next`
+    /// would return. The cursor does not move.
+    pub fn lap(&self) -> Lap<'_, T> {
+        Lap::new(&self.items, self.index)
+    }
+
+    /// Like `lap`, but moves the cursor past each item as it is yielded.
+    pub fn advance_lap(&mut self) -> AdvancingLap<'_, T> {
+        AdvancingLap::new(&self.items, &mut self.index)
+    }
+
+    /// The items in storage order. The cursor position is discarded.
+    pub fn into_inner(self) -> [T; N] {
+        self.items
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for CircularArray<T, N> {
+    fn from(items: [T; N]) -> Self {
+        Self::new(items)
+    }
+}
+
+impl<T, const N: usize> Deref for CircularArray<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for CircularArray<T, N> {
+    fn as_ref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: usize> Index<I> for CircularArray<T, N> {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &Self::Output {
+        Index::index(&self.items[..], index)
+    }
+}
+
+impl<T, I: SliceIndex<[T]>, const N: usize> IndexMut<I> for CircularArray<T, N> {
+    #[inline]
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(&mut self.items[..], index)
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a CircularArray<T, N> {
+    type Item = &'a T;
+    type IntoIter = Lap<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.lap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PRIMES: CircularArray<u32, 4> = CircularArray::starting_at([2, 3, 5, 7], 6);
+
+    #[test]
+    fn loop_through() {
+        let mut ca = CircularArray::new([50, 60, 70]);
+        assert_eq!(ca.next(), &50);
+        assert_eq!(ca.next(), &60);
+        assert_eq!(ca.next(), &70);
+        assert_eq!(ca.next(), &50);
+
+        *ca.next_mut() += 1;
+        assert_eq!(ca[1], 61);
+        ca.skip(7);
+        assert_eq!(ca.peek(), &50);
+        ca.rewind(1);
+        assert_eq!(ca.peek(), &70);
+        assert_eq!(ca.prev(), &61);
+        assert_eq!(ca.advance_lap().count(), 3);
+        assert_eq!(ca.position(), 1);
+    }
+
+    #[test]
+    fn const_construction() {
+        assert_eq!(PRIMES.position(), 2);
+        assert_eq!(PRIMES.peek(), &5);
+
+        let mut primes = PRIMES;
+        assert_eq!(primes.next(), &5);
+        assert_eq!(
+            primes.lap().copied().collect::<std::vec::Vec<_>>(),
+            [7, 2, 3, 5]
+        );
+        assert_eq!(primes.into_inner(), [2, 3, 5, 7]);
+    }
+}
@@ -0,0 +1,1860 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::{self as core_iter, FromIterator};
+use core::ops::{Deref, Index, IndexMut};
+use core::slice::{self, SliceIndex};
+
+use crate::array::NonEmptyArray;
+use crate::iter::{Advance, AdvancingLap, Lap};
+use crate::slice::{CircularSlice, CircularSliceMut};
+use crate::traversal::{gcd, mod_inverse, Direction, Position, TraversalMode};
+use crate::wrap;
+use crate::CircularVecError;
+
+/// See crate level documentation.
+///
+/// A CircularVec dereferences to a slice of its items in storage order,
+/// so read-only slice methods such as `len`, `iter` and `contains` are
+/// available directly.
+///
+/// The cursor is part of a CircularVec's value: `PartialEq`, `Hash` and
+/// `Ord` compare the items first and then the position of the cursor, so
+/// two CircularVecs holding the same items are only equal if `next` would
+/// return the same item from both. Compare `as_ref()` to ignore the cursor.
+///
+/// Each item also has an enabled flag, set with `enable` and `disable`.
+/// `next`, `next_mut`, `next_with_wrap`, their `try_` forms and
+/// `next_where` pass over disabled
+/// items, moving the cursor past them in ring order, and never look at
+/// more than one lap of items per call. Every other method, including
+/// `peek`, `prev` and the lap iterators, works on all items regardless of
+/// their flags. The flags are runtime state rather than part of the
+/// CircularVec's value, so comparisons, hashing and serialization
+/// ignore them.
+///
+/// By default `next` returns the items in order and wraps from the last
+/// item straight back to the first. `set_stride` makes it jump over
+/// items instead. `set_traversal_mode` switches to sweeping back and forth
+/// instead; see `TraversalMode`. In the bounce modes the cursor also has
+/// a direction, which `position` reports and which counts as part of the
+/// cursor for comparisons. Every other method that moves the cursor,
+/// such as `skip`, `prev` and `rewind`, follows the same sequence as
+/// `next`. The lap iterators always go round in storage order.
+///
+/// The CircularVec counts how many laps it has completed, which is how
+/// many times moving forwards has taken the cursor from the last item
+/// back to the first, or in the bounce modes, how many full sweeps there
+/// and back it has made. Hooks registered with `on_wrap` run each time
+/// that happens. Moving backwards never changes the count. Like the
+/// enabled flags, the lap count and hooks are not part of the value.
+///
+/// Invariant: `items` is never empty, so `cursor.index` always refers to
+/// an item, and `cursor.direction` is one the traversal mode can have at
+/// that index. `enabled` always has one flag per item, and `disabled`
+/// counts the items whose flag is false.
+pub struct CircularVec<T> {
+    pub(crate) items: Vec<T>,
+    pub(crate) cursor: CursorState,
+    enabled: Vec<bool>,
+    disabled: usize,
+}
+
+type WrapHook = Box<dyn FnMut(u64) + Send + Sync>;
+
+/// The lap count and the hooks to run when it goes up.
+#[derive(Default)]
+struct WrapState {
+    laps: u64,
+    hooks: Vec<WrapHook>,
+}
+
+impl WrapState {
+    fn add_laps(&mut self, laps: u64) {
+        if laps == 0 {
+            return;
+        }
+        self.laps = self.laps.saturating_add(laps);
+        for hook in &mut self.hooks {
+            hook(self.laps);
+        }
+    }
+}
+
+/// Everything about where the cursor is and how it moves.
+///
+/// Positions map one to one onto phases in `0..mode.period(len)`, so
+/// every movement is arithmetic on the phase. Each call to `next` moves
+/// the phase on by `stride`, which is never zero.
+pub(crate) struct CursorState {
+    pub(crate) index: usize,
+    pub(crate) direction: Direction,
+    mode: TraversalMode,
+    stride: isize,
+    wraps: WrapState,
+}
+
+impl Default for CursorState {
+    fn default() -> Self {
+        CursorState {
+            index: 0,
+            direction: Direction::Forward,
+            mode: TraversalMode::Wrap,
+            stride: 1,
+            wraps: WrapState::default(),
+        }
+    }
+}
+
+impl CursorState {
+    fn position(&self) -> Position {
+        Position {
+            index: self.index,
+            direction: self.direction,
+        }
+    }
+
+    fn phase(&self, len: usize) -> usize {
+        self.mode.phase_of(self.position(), len)
+    }
+
+    fn set_phase(&mut self, phase: usize, len: usize) {
+        let Position { index, direction } = self.mode.position_at(phase, len);
+        self.index = index;
+        self.direction = direction;
+    }
+
+    /// Fix up the direction after the index, the length or the mode has
+    /// changed. `index` must already be in bounds.
+    pub(crate) fn normalize(&mut self, len: usize) {
+        self.set_phase(self.phase(len), len);
+    }
+
+    /// The phase after `calls` calls to `next`, or to `prev` if
+    /// `backwards`, and how many times the phase passes zero on the way.
+    fn phase_after(&self, calls: usize, backwards: bool, len: usize) -> (usize, u64) {
+        let period = self.mode.period(len);
+        let phase = self.phase(len);
+        // Cannot overflow: both factors are below 2^64.
+        let distance = calls as u128 * self.stride.unsigned_abs() as u128;
+        let mut laps = distance / period as u128;
+        let rest = (distance % period as u128) as usize;
+        let phase = if (self.stride < 0) == backwards {
+            if rest >= period - phase {
+                laps += 1;
+            }
+            wrap::forward(phase, rest, period)
+        } else {
+            if phase > 0 && rest >= phase {
+                laps += 1;
+            }
+            wrap::backward(phase, rest, period)
+        };
+        (phase, u64::try_from(laps).unwrap_or(u64::MAX))
+    }
+
+    /// The index of the item the cursor points at after `calls` calls to
+    /// `next`, or to `prev` if `backwards`.
+    fn index_after(&self, calls: usize, backwards: bool, len: usize) -> usize {
+        let (phase, _) = self.phase_after(calls, backwards, len);
+        self.mode.position_at(phase, len).index
+    }
+
+    /// Move on as `calls` calls to `next` would, counting any laps
+    /// completed on the way. Returns whether at least one lap was
+    /// completed.
+    fn forward(&mut self, calls: usize, len: usize) -> bool {
+        let (phase, laps) = self.phase_after(calls, false, len);
+        self.set_phase(phase, len);
+        self.wraps.add_laps(laps);
+        laps > 0
+    }
+
+    /// Move back as `calls` calls to `prev` would.
+    fn backward(&mut self, calls: usize, len: usize) {
+        let (phase, _) = self.phase_after(calls, true, len);
+        self.set_phase(phase, len);
+    }
+
+    /// The calls to `next` after which the cursor first points at each item
+    /// it can reach, in order, with the index of that item. The bounce
+    /// modes reach most items twice per period, once each way, and only
+    /// the first of the two is included.
+    fn first_visits(&self, len: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let period = self.mode.period(len);
+        let start = self.phase(len);
+        let step = match self.stride.unsigned_abs() % period {
+            step if self.stride < 0 => (period - step) % period,
+            step => step,
+        };
+        let orbit = self.orbit_len(len);
+        // Only phases a multiple of `spacing` away from `start` are ever
+        // reached, and `inverse` turns that multiple into a number of calls.
+        let spacing = period / orbit;
+        let inverse = mod_inverse(step / spacing, orbit);
+        let calls_to = move |phase: usize| {
+            let distance = (phase + period - start) % period;
+            (distance % spacing == 0)
+                .then(|| (distance / spacing) as u128 * inverse as u128 % orbit as u128)
+        };
+        (0..orbit).filter_map(move |calls| {
+            let phase = ((start as u128 + calls as u128 * step as u128) % period as u128) as usize;
+            let mirror = self.mode.mirror(phase, len);
+            match calls_to(mirror) {
+                Some(earlier) if mirror != phase && earlier < calls as u128 => None,
+                _ => Some((calls, self.mode.position_at(phase, len).index)),
+            }
+        })
+    }
+
+    /// How many calls to `next` it takes for the phase to come back to
+    /// where it is now.
+    fn orbit_len(&self, len: usize) -> usize {
+        let period = self.mode.period(len);
+        period / gcd(self.stride.unsigned_abs() % period, period)
+    }
+}
+
+impl Advance for CursorState {
+    fn advance(&mut self, len: usize) -> usize {
+        let index = self.index;
+        self.forward(1, len);
+        index
+    }
+}
+
+impl<T> CircularVec<T> {
+    /// Create a CircularVec from a first item and any number of further
+    /// items. Because the first item is required, this cannot fail.
+    pub fn new<I: IntoIterator<Item = T>>(first: T, rest: I) -> Self {
+        let mut items = vec![first];
+        items.extend(rest);
+        Self::from_vec_unchecked(items)
+    }
+
+    /// Create a CircularVec from an iterator, returning
+    /// `CircularVecError::Empty` if the iterator yields no items.
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, CircularVecError> {
+        Self::try_from(iter.into_iter().collect::<Vec<T>>())
+    }
+
+    fn from_vec_unchecked(items: Vec<T>) -> Self {
+        Self::from_parts(items, 0)
+    }
+
+    pub(crate) fn from_parts(items: Vec<T>, index: usize) -> Self {
+        debug_assert!(index < items.len());
+        CircularVec {
+            enabled: vec![true; items.len()],
+            disabled: 0,
+            items,
+            cursor: CursorState {
+                index,
+                ..CursorState::default()
+            },
+        }
+    }
+
+    /// Set the traversal mode, returning the CircularVec. See
+    /// `set_traversal_mode`.
+    ///
+    ///     # use circular_vec::{CircularVec, TraversalMode};
+    ///     let mut frames = CircularVec::from([0, 1, 2, 3]).with_traversal_mode(TraversalMode::Bounce);
+    ///     let sweep: Vec<_> = (0..8).map(|_| *frames.next()).collect();
+    ///     assert_eq!(sweep, [0, 1, 2, 3, 2, 1, 0, 1]);
+    pub fn with_traversal_mode(mut self, mode: TraversalMode) -> Self {
+        self.set_traversal_mode(mode);
+        self
+    }
+
+    /// The order in which `next` visits the items.
+    pub fn traversal_mode(&self) -> TraversalMode {
+        self.cursor.mode
+    }
+
+    /// Change the order in which `next` visits the items. The cursor
+    /// stays on the same item and keeps its direction where the new mode
+    /// allows it; switching to `TraversalMode::Wrap` always makes it
+    /// `Direction::Forward`.
+    pub fn set_traversal_mode(&mut self, mode: TraversalMode) {
+        self.cursor.mode = mode;
+        self.cursor.normalize(self.items.len());
+    }
+
+    /// Set the stride, returning the CircularVec. See `set_stride`.
+    ///
+    ///     # use circular_vec::CircularVec;
+    ///     let mut shards = CircularVec::new(0, 1..5).with_stride(2);
+    ///     let order: Vec<_> = (0..5).map(|_| *shards.next()).collect();
+    ///     assert_eq!(order, [0, 2, 4, 1, 3]);
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is zero.
+    pub fn with_stride(mut self, stride: isize) -> Self {
+        if let Err(e) = self.set_stride(stride) {
+            panic!("{}", e);
+        }
+        self
+    }
+
+    /// How far each call to `next` moves the cursor through the sequence
+    /// of the traversal mode. The default is 1.
+    pub fn stride(&self) -> isize {
+        self.cursor.stride
+    }
+
+    /// Make each call to `next` move the cursor `stride` places through
+    /// the sequence of the traversal mode, so `next` returns every
+    /// `stride`th item, wrapping around as usual. A negative stride goes
+    /// through the sequence backwards. `prev`, `skip` and the other
+    /// methods that move the cursor are scaled to match. A lap is counted
+    /// each time `next` passes over the start of the sequence, so with a
+    /// stride of `k` there are `k` laps in every full sequence's worth of
+    /// calls.
+    ///
+    /// A stride that shares a factor with the length of the sequence
+    /// means `next` never returns some of the items: with 6 items and a
+    /// stride of 4, it only returns the items at even indexes. Check
+    /// `visits_every_item`, or pick a stride with `coprime_stride`.
+    ///
+    /// Returns `CircularVecError::ZeroStride` and leaves the stride
+    /// untouched if `stride` is zero.
+    pub fn set_stride(&mut self, stride: isize) -> Result<(), CircularVecError> {
+        if stride == 0 {
+            return Err(CircularVecError::ZeroStride);
+        }
+        self.cursor.stride = stride;
+        Ok(())
+    }
+
+    /// How many different items `next` returns, starting from the current
+    /// position and ignoring enabled flags, before the sequence repeats.
+    /// This is every item unless the stride shares a factor with the
+    /// length of the sequence.
+    pub fn reachable_items(&self) -> usize {
+        let mut reachable = 0;
+        self.find_forward(|_| {
+            reachable += 1;
+            false
+        });
+        reachable
+    }
+
+    /// Whether `next` returns every item, ignoring enabled flags, with
+    /// the current stride and traversal mode. See `set_stride`.
+    pub fn visits_every_item(&self) -> bool {
+        self.reachable_items() == self.items.len()
+    }
+
+    /// The stride closest to `stride`, searching away from zero, with
+    /// which `next` returns every item in the current traversal mode. The
+    /// sign is kept, and a `stride` of zero is treated as 1. Near the
+    /// limits of `isize`, where there may be no such stride further from
+    /// zero, it searches towards zero instead.
+    ///
+    ///     # use circular_vec::CircularVec;
+    ///     let cv = CircularVec::new(0, 1..6);
+    ///     assert_eq!(cv.coprime_stride(4), 5);
+    ///     assert_eq!(cv.coprime_stride(-2), -5);
+    pub fn coprime_stride(&self, stride: isize) -> isize {
+        let period = self.cursor.mode.period(self.items.len());
+        let coprime = |magnitude: &usize| gcd(*magnitude, period) == 1;
+        let limit = isize::MAX as usize;
+        let start = stride.unsigned_abs().clamp(1, limit);
+        let magnitude = (start..=limit)
+            .find(coprime)
+            .or_else(|| (1..start).rev().find(coprime))
+            .expect("1 is coprime with every period") as isize;
+        if stride < 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Get an immutable reference to the next enabled item in the
+    /// CircularVec, passing over any disabled items.
+    ///
+    /// A CircularVec is never empty, so as long as no items have been
+    /// disabled there is always a next item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every item is disabled. Use `try_next` to handle that
+    /// case without panicking.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &T {
+        match self.try_next() {
+            Ok(item) => item,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Get an immutable reference to the next enabled item, passing over
+    /// any disabled items, or `CircularVecError::AllDisabled` if there is
+    /// none. The cursor does not move if there is no enabled item.
+    pub fn try_next(&mut self) -> Result<&T, CircularVecError> {
+        let (steps, index) = self.next_enabled().ok_or(CircularVecError::AllDisabled)?;
+        self.move_forward(steps + 1);
+        Ok(&self.items[index])
+    }
+
+    /// Like `next`, but also returns whether this call completed a lap:
+    /// true when it moved the cursor from the last item back to the first,
+    /// or in the bounce modes, back to the first item moving forwards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every item is disabled. Use `try_next_with_wrap` to
+    /// handle that case without panicking.
+    pub fn next_with_wrap(&mut self) -> (&T, bool) {
+        match self.try_next_with_wrap() {
+            Ok(next) => next,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Like `try_next`, but also returns whether this call completed a
+    /// lap. See `next_with_wrap`.
+    pub fn try_next_with_wrap(&mut self) -> Result<(&T, bool), CircularVecError> {
+        let (steps, index) = self.next_enabled().ok_or(CircularVecError::AllDisabled)?;
+        let wrapped = self.move_forward(steps + 1);
+        Ok((&self.items[index], wrapped))
+    }
+
+    /// How many laps have been completed: how many times moving forwards
+    /// has taken the cursor from the last item back to the first, or in
+    /// the bounce modes, back to the first item moving forwards.
+    pub fn laps_completed(&self) -> u64 {
+        self.cursor.wraps.laps
+    }
+
+    /// Register a hook to run each time a lap is completed, for example to
+    /// rotate logs or flush per-lap statistics. It is passed the new value
+    /// of `laps_completed`.
+    ///
+    /// Hooks run in the order they were registered, from inside the call
+    /// that moved the cursor. A single `skip` that completes several laps
+    /// runs each hook once, with the count already including every lap.
+    pub fn on_wrap<F: FnMut(u64) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.cursor.wraps.hooks.push(Box::new(hook));
+    }
+
+    /// Remove every hook registered with `on_wrap`.
+    pub fn clear_wrap_hooks(&mut self) {
+        self.cursor.wraps.hooks.clear();
+    }
+
+    /// Get an immutable reference to the next enabled item for which
+    /// `pred` returns true, passing over every item before it. Returns
+    /// `None` without moving the cursor if no enabled item matches.
+    ///
+    /// `pred` is called at most once per item, in the order `next` would
+    /// visit them starting at the cursor.
+    pub fn next_where<F: FnMut(&T) -> bool>(&mut self, mut pred: F) -> Option<&T> {
+        let items = &self.items;
+        let enabled = &self.enabled;
+        let (steps, index) = self.find_forward(|i| enabled[i] && pred(&items[i]))?;
+        self.move_forward(steps + 1);
+        Some(&self.items[index])
+    }
+
+    /// Stop `next` and its relatives from returning the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn disable(&mut self, index: usize) {
+        if self.enabled[index] {
+            self.enabled[index] = false;
+            self.disabled += 1;
+        }
+    }
+
+    /// Let `next` and its relatives return the item at `index` again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn enable(&mut self, index: usize) {
+        if !self.enabled[index] {
+            self.enabled[index] = true;
+            self.disabled -= 1;
+        }
+    }
+
+    /// Whether the item at `index` is enabled. Items are enabled when
+    /// they are added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn is_enabled(&self, index: usize) -> bool {
+        self.enabled[index]
+    }
+
+    /// Move the cursor on as `n` calls to `next` would, without returning
+    /// any items or looking at enabled flags. This takes constant time
+    /// regardless of `n`.
+    pub fn skip(&mut self, n: usize) {
+        self.move_forward(n);
+    }
+
+    /// Move the cursor back by `n` items, the same as calling `prev`
+    /// `n` times. This takes constant time regardless of `n`.
+    pub fn rewind(&mut self, n: usize) {
+        self.cursor.backward(n, self.items.len());
+    }
+
+    /// Move the cursor by a signed amount: forwards like `skip` when `n`
+    /// is positive and backwards like `rewind` when it is negative.
+    pub fn advance_by(&mut self, n: isize) {
+        if n >= 0 {
+            self.skip(n as usize);
+        } else {
+            self.rewind(n.unsigned_abs());
+        }
+    }
+
+    /// Get a mutable reference to the next enabled item in the
+    /// CircularVec, passing over any disabled items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every item is disabled. Use `try_next_mut` to handle
+    /// that case without panicking.
+    pub fn next_mut(&mut self) -> &mut T {
+        match self.try_next_mut() {
+            Ok(item) => item,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Get a mutable reference to the next enabled item, passing over any
+    /// disabled items, or `CircularVecError::AllDisabled` if there is
+    /// none. The cursor does not move if there is no enabled item.
+    pub fn try_next_mut(&mut self) -> Result<&mut T, CircularVecError> {
+        let (steps, index) = self.next_enabled().ok_or(CircularVecError::AllDisabled)?;
+        self.move_forward(steps + 1);
+        Ok(IndexMut::index_mut(&mut *self.items, index))
+    }
+
+    /// Step the cursor backwards and get an immutable reference to the item
+    /// it now points at. This is the inverse of `next`: calling `next` and
+    /// then `prev` returns the same item twice and leaves the position as
+    /// it was.
+    pub fn prev(&mut self) -> &T {
+        self.cursor.backward(1, self.items.len());
+        &self.items[self.cursor.index]
+    }
+
+    /// Step the cursor backwards and get a mutable reference to the item
+    /// it now points at. See `prev`.
+    pub fn prev_mut(&mut self) -> &mut T {
+        self.cursor.backward(1, self.items.len());
+        IndexMut::index_mut(&mut *self.items, self.cursor.index)
+    }
+
+    /// Get an immutable reference to the item `next` would return,
+    /// without moving the cursor.
+    pub fn peek(&self) -> &T {
+        &self.items[self.cursor.index]
+    }
+
+    /// Get a mutable reference to the item `next` would return,
+    /// without moving the cursor.
+    pub fn peek_mut(&mut self) -> &mut T {
+        IndexMut::index_mut(&mut *self.items, self.cursor.index)
+    }
+
+    /// Get an immutable reference to the item `prev` would return,
+    /// without moving the cursor.
+    pub fn peek_back(&self) -> &T {
+        &self.items[self.cursor.index_after(1, true, self.items.len())]
+    }
+
+    /// Get an immutable reference to the item at `index`, counting from
+    /// the end if it is negative and wrapping around in either direction,
+    /// so this never panics. `-1` is the last item.
+    pub fn get_wrapping(&self, index: isize) -> &T {
+        &self.items[self.wrapping_index(0, index)]
+    }
+
+    /// Get a mutable reference to the item at `index`. See `get_wrapping`.
+    pub fn get_wrapping_mut(&mut self, index: isize) -> &mut T {
+        let index = self.wrapping_index(0, index);
+        IndexMut::index_mut(&mut *self.items, index)
+    }
+
+    /// Get an immutable reference to the item `offset` places after the
+    /// one `next` would return, or before it if `offset` is negative,
+    /// wrapping around in storage order. `get_relative(0)` is the same as
+    /// `peek`. This never panics and does not look at the traversal mode
+    /// or stride.
+    pub fn get_relative(&self, offset: isize) -> &T {
+        &self.items[self.wrapping_index(self.cursor.index, offset)]
+    }
+
+    /// Get a mutable reference to the item `offset` places from the one
+    /// `next` would return. See `get_relative`.
+    pub fn get_relative_mut(&mut self, offset: isize) -> &mut T {
+        let index = self.wrapping_index(self.cursor.index, offset);
+        IndexMut::index_mut(&mut *self.items, index)
+    }
+
+    /// A view of the `len` items starting at index `start`, wrapping
+    /// around from the last item to the first if the run reaches the end.
+    /// `start` wraps around too, so it may be past the end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the number of items, because the
+    /// view would have to hold some items twice.
+    pub fn range_wrapping(&self, start: usize, len: usize) -> CircularSlice<'_, T> {
+        let start = self.check_range(start, len);
+        let (head, tail) = self.items.split_at(start);
+        if len <= tail.len() {
+            CircularSlice::new(&tail[..len], &[])
+        } else {
+            CircularSlice::new(tail, &head[..len - tail.len()])
+        }
+    }
+
+    /// A mutable view of the `len` items starting at index `start`. See
+    /// `range_wrapping`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the number of items.
+    pub fn range_wrapping_mut(&mut self, start: usize, len: usize) -> CircularSliceMut<'_, T> {
+        let start = self.check_range(start, len);
+        let (head, tail) = self.items.split_at_mut(start);
+        if len <= tail.len() {
+            CircularSliceMut::new(&mut tail[..len], &mut [])
+        } else {
+            let wrapped = len - tail.len();
+            CircularSliceMut::new(tail, &mut head[..wrapped])
+        }
+    }
+
+    /// The index of the item `next` would return, and the direction the
+    /// cursor is sweeping in. The direction is always
+    /// `Direction::Forward` in `TraversalMode::Wrap`.
+    pub fn position(&self) -> Position {
+        self.cursor.position()
+    }
+
+    /// Move the cursor so that `next` returns the item at `index`.
+    /// Indexes past the end wrap around, so this never panics. The
+    /// cursor keeps its direction where the traversal mode allows it.
+    pub fn set_position(&mut self, index: usize) {
+        self.cursor.index = index % self.items.len();
+        self.cursor.normalize(self.items.len());
+    }
+
+    /// Move the cursor back to the first item, moving forwards.
+    pub fn reset(&mut self) {
+        self.cursor.index = 0;
+        self.cursor.direction = Direction::Forward;
+    }
+
+    /// Iterate over the items in storage order, ignoring the cursor.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Iterate mutably over the items in storage order, ignoring the cursor.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.items.iter_mut()
+    }
+
+    /// Iterate over every item exactly once, starting with the item `next`
+    /// would return and going round in storage order whatever the
+    /// traversal mode. The cursor does not move.
+    pub fn lap(&self) -> Lap<'_, T> {
+        Lap::new(&self.items, self.cursor.index)
+    }
+
+    /// Yield as many items as there are, moving the cursor past each one
+    /// as `next` would. Unlike `lap`, this follows the traversal mode.
+    /// See `AdvancingLap` for where the cursor ends up.
+    pub fn advance_lap(&mut self) -> AdvancingLap<'_, T> {
+        AdvancingLap::new(&self.items, &mut self.cursor)
+    }
+
+    /// Iterate over `n` laps, starting at the cursor, without moving it.
+    pub fn laps(&self, n: usize) -> impl Iterator<Item = &T> {
+        core_iter::repeat_n(self.lap(), n).flatten()
+    }
+
+    /// Iterate over the items forever, starting at the cursor, without
+    /// moving it. Unlike every other iterator here, this never ends.
+    pub fn cycle(&self) -> impl Iterator<Item = &T> {
+        self.lap().cycle()
+    }
+
+    /// Append an item to the end of the storage. The cursor keeps
+    /// pointing at the same next item.
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+        self.enabled.push(true);
+        self.cursor.normalize(self.items.len());
+    }
+
+    /// Insert an item at `index`, shifting later items along. The cursor
+    /// keeps pointing at the same next item, so an item inserted at the
+    /// cursor's position is only reached after a full lap. Use
+    /// `insert_at_cursor` to make it the next item instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of items.
+    pub fn insert(&mut self, index: usize, item: T) {
+        self.items.insert(index, item);
+        self.enabled.insert(index, true);
+        if index <= self.cursor.index {
+            self.cursor.index += 1;
+        }
+        self.cursor.normalize(self.items.len());
+    }
+
+    /// Insert an item so that it is the one `next` returns. During a
+    /// backward sweep the item goes just after the next one in storage,
+    /// so the item that was next still comes straight after it.
+    pub fn insert_at_cursor(&mut self, item: T) {
+        if self.cursor.direction == Direction::Backward {
+            self.cursor.index += 1;
+        }
+        self.items.insert(self.cursor.index, item);
+        self.enabled.insert(self.cursor.index, true);
+        self.cursor.normalize(self.items.len());
+    }
+
+    /// Remove and return the item at `index`, shifting later items back.
+    /// If the removed item was the next one, the cursor moves on to the
+    /// item that followed it in the current sweep.
+    ///
+    /// Returns `CircularVecError::Empty` and leaves the CircularVec
+    /// untouched if this is the only item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<T, CircularVecError> {
+        self.check_removable(index)?;
+        let item = self.items.remove(index);
+        if !self.enabled.remove(index) {
+            self.disabled -= 1;
+        }
+        if index == self.cursor.index && self.cursor.direction == Direction::Backward {
+            // The item before it follows it, unless the sweep turns round
+            // here, in which case the item after it now has its index.
+            if index > 0 {
+                self.cursor.index -= 1;
+            } else {
+                self.cursor.direction = Direction::Forward;
+            }
+        } else if index < self.cursor.index {
+            self.cursor.index -= 1;
+        }
+        self.cursor.index %= self.items.len();
+        self.cursor.normalize(self.items.len());
+        Ok(item)
+    }
+
+    /// Remove and return the item `next` would have returned, moving the
+    /// cursor on to the item after it.
+    ///
+    /// Returns `CircularVecError::Empty` if this is the only item.
+    pub fn remove_at_cursor(&mut self) -> Result<T, CircularVecError> {
+        self.remove(self.cursor.index)
+    }
+
+    /// Remove and return the item at `index`, replacing it with the last
+    /// item. This is O(1) but does not preserve storage order. The cursor
+    /// keeps pointing at the same next item, or the item that followed
+    /// the removed one if that was the next item.
+    ///
+    /// Returns `CircularVecError::Empty` and leaves the CircularVec
+    /// untouched if this is the only item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> Result<T, CircularVecError> {
+        self.check_removable(index)?;
+        let last = self.items.len() - 1;
+        // The item that will be next once the removal is done, by its
+        // index before the removal.
+        let next = if index != self.cursor.index {
+            self.cursor.index
+        } else if self.cursor.direction == Direction::Backward && index > 0 {
+            index - 1
+        } else {
+            self.cursor.direction = Direction::Forward;
+            wrap::increment(index, self.items.len())
+        };
+        let item = self.items.swap_remove(index);
+        if !self.enabled.swap_remove(index) {
+            self.disabled -= 1;
+        }
+        self.cursor.index = if next == last { index } else { next } % self.items.len();
+        self.cursor.normalize(self.items.len());
+        Ok(item)
+    }
+
+    /// Shorten the CircularVec to `len` items, dropping the rest. If the
+    /// next item is dropped, the cursor wraps around to the first item.
+    /// This has no effect if `len` is at least the current length.
+    ///
+    /// Returns `CircularVecError::Empty` and leaves the CircularVec
+    /// untouched if `len` is zero.
+    pub fn truncate(&mut self, len: usize) -> Result<(), CircularVecError> {
+        if len == 0 {
+            return Err(CircularVecError::Empty);
+        }
+        self.items.truncate(len);
+        self.enabled.truncate(len);
+        self.disabled = self.enabled.iter().filter(|&&e| !e).count();
+        if self.cursor.index >= self.items.len() {
+            self.cursor.index = 0;
+        }
+        self.cursor.normalize(self.items.len());
+        Ok(())
+    }
+
+    /// Keep only the items for which `f` returns true. If the next item is
+    /// removed, the cursor moves on to the first kept item after it in the
+    /// current sweep.
+    ///
+    /// `f` is called exactly once per item, in storage order. Returns
+    /// `CircularVecError::Empty` and leaves the CircularVec untouched if
+    /// no item would be kept.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) -> Result<(), CircularVecError> {
+        let keep: Vec<bool> = self.items.iter().map(&mut f).collect();
+        let kept = keep.iter().filter(|&&k| k).count();
+        if kept == 0 {
+            return Err(CircularVecError::Empty);
+        }
+        let kept_before_cursor = keep[..self.cursor.index].iter().filter(|&&k| k).count();
+        let cursor_kept = keep[self.cursor.index];
+        let mut keep_items = keep.iter();
+        self.items.retain(|_| keep_items.next() == Some(&true));
+        let mut keep_flags = keep.iter();
+        self.enabled.retain(|_| keep_flags.next() == Some(&true));
+        self.disabled = self.enabled.iter().filter(|&&e| !e).count();
+        self.cursor.index = match self.cursor.direction {
+            Direction::Backward if !cursor_kept && kept_before_cursor > 0 => kept_before_cursor - 1,
+            Direction::Backward if !cursor_kept => {
+                // No kept item before the cursor, so the sweep turns round.
+                self.cursor.direction = Direction::Forward;
+                0
+            }
+            _ => kept_before_cursor % kept,
+        };
+        self.cursor.normalize(kept);
+        Ok(())
+    }
+
+    /// Rotate the storage `k` places to the left, so the item at index
+    /// `k` moves to index 0, wrapping `k` around if it is past the end.
+    /// The cursor moves with the items, so `next` returns the same item
+    /// as before. Enabled flags move with their items too.
+    ///
+    /// In the bounce modes the cursor turns around at the ends of the
+    /// storage, so after a rotation the items after the next one may
+    /// come in a different order.
+    pub fn rotate_left(&mut self, k: usize) {
+        let len = self.items.len();
+        let k = k % len;
+        self.items.rotate_left(k);
+        self.enabled.rotate_left(k);
+        self.cursor.index = wrap::backward(self.cursor.index, k, len);
+        self.cursor.normalize(len);
+    }
+
+    /// Rotate the storage `k` places to the right, so the item at index 0
+    /// moves to index `k`, wrapping `k` around if it is past the end. See
+    /// `rotate_left`.
+    pub fn rotate_right(&mut self, k: usize) {
+        let len = self.items.len();
+        self.rotate_left(len - k % len);
+    }
+
+    /// Rotate the storage so that the item `next` would return is at
+    /// index 0 and the cursor points at it. Afterwards indexing,
+    /// `iter` and serialization all see the items starting from the
+    /// cursor, and `next` still returns the same item.
+    ///
+    ///     # use circular_vec::CircularVec;
+    ///     let mut cv = CircularVec::from(['a', 'b', 'c']);
+    ///     cv.next();
+    ///     cv.rotate_to_cursor();
+    ///     assert_eq!(cv.as_ref(), ['b', 'c', 'a']);
+    ///     assert_eq!(cv.next(), &'b');
+    pub fn rotate_to_cursor(&mut self) {
+        self.rotate_left(self.cursor.index);
+    }
+
+    /// `from` moved by a signed `offset`, wrapping around the items.
+    fn wrapping_index(&self, from: usize, offset: isize) -> usize {
+        let len = self.items.len();
+        if offset >= 0 {
+            wrap::forward(from, offset as usize, len)
+        } else {
+            wrap::backward(from, offset.unsigned_abs(), len)
+        }
+    }
+
+    /// Wraps `start` into bounds after checking that a run of `len` items
+    /// fits.
+    fn check_range(&self, start: usize, len: usize) -> usize {
+        assert!(
+            len <= self.items.len(),
+            "range of {} items is longer than the {} items",
+            len,
+            self.items.len()
+        );
+        start % self.items.len()
+    }
+
+    fn check_removable(&self, index: usize) -> Result<(), CircularVecError> {
+        assert!(
+            index < self.items.len(),
+            "removal index (is {}) should be < len (is {})",
+            index,
+            self.items.len()
+        );
+        if self.items.len() == 1 {
+            return Err(CircularVecError::Empty);
+        }
+        Ok(())
+    }
+
+    /// Move the cursor forwards by `steps`, counting any laps completed on
+    /// the way. Returns whether at least one lap was completed.
+    fn move_forward(&mut self, steps: usize) -> bool {
+        self.cursor.forward(steps, self.items.len())
+    }
+
+    /// How many items `next` would pass over before reaching the next
+    /// enabled item, and the index of that item.
+    fn next_enabled(&self) -> Option<(usize, usize)> {
+        if self.disabled == 0 {
+            return Some((0, self.cursor.index));
+        }
+        self.find_forward(|i| self.enabled[i])
+    }
+
+    /// The first index, in the order `next` would visit them, that
+    /// `accept` returns true for, along with how many items `next` would
+    /// pass over before reaching it. Looks at each index at most once.
+    pub(crate) fn find_forward<F: FnMut(usize) -> bool>(
+        &self,
+        mut accept: F,
+    ) -> Option<(usize, usize)> {
+        self.cursor
+            .first_visits(self.items.len())
+            .find(|&(_, index)| accept(index))
+    }
+}
+
+/// The clone keeps the enabled flags and lap count, but starts without
+/// any wrap hooks, because boxed closures cannot be cloned.
+impl<T: Clone> Clone for CircularVec<T> {
+    fn clone(&self) -> Self {
+        CircularVec {
+            items: self.items.clone(),
+            cursor: CursorState {
+                index: self.cursor.index,
+                direction: self.cursor.direction,
+                mode: self.cursor.mode,
+                stride: self.cursor.stride,
+                wraps: WrapState {
+                    laps: self.cursor.wraps.laps,
+                    hooks: Vec::new(),
+                },
+            },
+            enabled: self.enabled.clone(),
+            disabled: self.disabled,
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CircularVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircularVec")
+            .field("items", &self.items)
+            .field("index", &self.cursor.index)
+            .field("direction", &self.cursor.direction)
+            .field("mode", &self.cursor.mode)
+            .field("stride", &self.cursor.stride)
+            .field("enabled", &self.enabled)
+            .field("laps", &self.cursor.wraps.laps)
+            .finish()
+    }
+}
+
+/// There is deliberately no `From<Vec<T>>`: it would have to panic on an
+/// empty Vec, and it would conflict with this impl.
+impl<T> TryFrom<Vec<T>> for CircularVec<T> {
+    type Error = CircularVecError;
+
+    fn try_from(items: Vec<T>) -> Result<Self, Self::Error> {
+        if items.is_empty() {
+            return Err(CircularVecError::Empty);
+        }
+        Ok(Self::from_vec_unchecked(items))
+    }
+}
+
+/// # Panics
+///
+/// Panics if the iterator yields no items. Use `CircularVec::try_from_iter`
+/// to handle empty input without panicking.
+impl<T> FromIterator<T> for CircularVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        match Self::try_from_iter(iter) {
+            Ok(cv) => cv,
+            Err(e) => panic!("cannot collect into CircularVec: {}", e),
+        }
+    }
+}
+
+/// A single lap starting at the cursor, as returned by `CircularVec::lap`.
+impl<'a, T> IntoIterator for &'a CircularVec<T> {
+    type Item = &'a T;
+    type IntoIter = Lap<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.lap()
+    }
+}
+
+impl<T, I: SliceIndex<[T]>> Index<I> for CircularVec<T> {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &Self::Output {
+        Index::index(&*self.items, index)
+    }
+}
+
+impl<T, I: SliceIndex<[T]>> IndexMut<I> for CircularVec<T> {
+    #[inline]
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        IndexMut::index_mut(&mut *self.items, index)
+    }
+}
+
+/// An index into a CircularVec that wraps around instead of panicking,
+/// counting from the end when it is negative.
+///
+///     # use circular_vec::{CircularVec, Wrapping};
+///     let cv = CircularVec::from([1, 2, 3]);
+///     assert_eq!(cv[Wrapping(-1)], 3);
+///     assert_eq!(cv[Wrapping(4)], 2);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Wrapping(pub isize);
+
+impl<T> Index<Wrapping> for CircularVec<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: Wrapping) -> &T {
+        self.get_wrapping(index.0)
+    }
+}
+
+impl<T> IndexMut<Wrapping> for CircularVec<T> {
+    #[inline]
+    fn index_mut(&mut self, index: Wrapping) -> &mut T {
+        self.get_wrapping_mut(index.0)
+    }
+}
+
+impl<T> Deref for CircularVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T> AsRef<[T]> for CircularVec<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+/// Items are appended to the end of the storage, like `push`.
+impl<T> Extend<T> for CircularVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.items.extend(iter);
+        self.enabled.resize(self.items.len(), true);
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for CircularVec<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// A CircularVec holding a single default item.
+impl<T: Default> Default for CircularVec<T> {
+    fn default() -> Self {
+        Self::new(T::default(), None)
+    }
+}
+
+impl<T: PartialEq> PartialEq for CircularVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items && self.position() == other.position()
+    }
+}
+
+impl<T: Eq> Eq for CircularVec<T> {}
+
+impl<T: Hash> Hash for CircularVec<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.items.hash(state);
+        self.position().hash(state);
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for CircularVec<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.items.partial_cmp(&other.items) {
+            Some(Ordering::Equal) => self.position().partial_cmp(&other.position()),
+            ord => ord,
+        }
+    }
+}
+
+impl<T: Ord> Ord for CircularVec<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.items
+            .cmp(&other.items)
+            .then_with(|| self.position().cmp(&other.position()))
+    }
+}
+
+/// Converting an empty array is rejected at compile time.
+impl<T, const N: usize> From<[T; N]> for CircularVec<T> {
+    fn from(items: [T; N]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = NonEmptyArray::<N>::CHECK;
+        Self::from_vec_unchecked(Vec::from(items))
+    }
+}
+
+impl<T> TryFrom<Box<[T]>> for CircularVec<T> {
+    type Error = CircularVecError;
+
+    fn try_from(items: Box<[T]>) -> Result<Self, Self::Error> {
+        Self::try_from(items.into_vec())
+    }
+}
+
+/// The items in storage order. The cursor position is discarded.
+impl<T> From<CircularVec<T>> for Vec<T> {
+    fn from(cv: CircularVec<T>) -> Self {
+        cv.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::{String, ToString};
+    use core::mem;
+
+    #[test]
+    fn loop_through() {
+        let mut cv: CircularVec<u64> = [50, 60, 70, 80].to_vec().into_iter().collect();
+        assert_eq!(cv.next(), &50);
+        assert_eq!(cv.next(), &60);
+        assert_eq!(cv.next(), &70);
+        assert_eq!(cv.next(), &80);
+        assert_eq!(cv.next(), &50);
+
+        assert_eq!(cv[0], 50);
+    }
+
+    #[test]
+    fn loop_through_string() {
+        let mut cv: CircularVec<String> = ["hello".to_string(), "world".to_string()]
+            .to_vec()
+            .into_iter()
+            .collect();
+
+        assert_eq!(cv.next(), "hello");
+        assert_eq!(cv.next(), "world");
+        assert_eq!(cv.next(), "hello");
+        assert_eq!(cv.next(), "world");
+        assert_eq!(cv.next(), "hello");
+        assert_eq!(cv.next(), "world");
+
+        assert_eq!(cv[0], "hello");
+    }
+
+    #[test]
+    fn step_backwards() {
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        assert_eq!(cv.prev(), &3);
+        assert_eq!(cv.prev(), &2);
+        assert_eq!(cv.next(), &2);
+        assert_eq!(cv.next(), &3);
+        assert_eq!(cv.next(), &1);
+        assert_eq!(cv.prev(), &1);
+        assert_eq!(cv.position().index, 0);
+
+        *cv.prev_mut() += 10;
+        assert_eq!(cv[2], 13);
+    }
+
+    #[test]
+    fn peek_and_seek() {
+        let mut cv = CircularVec::new('a', vec!['b', 'c']);
+        assert_eq!(cv.peek(), &'a');
+        assert_eq!(cv.peek_back(), &'c');
+        assert_eq!(cv.position().index, 0);
+
+        cv.set_position(7);
+        assert_eq!(cv.position().index, 1);
+        assert_eq!(cv.peek(), &'b');
+        assert_eq!(cv.peek_back(), &'a');
+        *cv.peek_mut() = 'B';
+        assert_eq!(cv.next(), &'B');
+
+        cv.reset();
+        assert_eq!(cv.next(), &'a');
+    }
+
+    #[test]
+    fn skip_and_rewind() {
+        let mut cv = CircularVec::new(0, 1..5);
+        cv.skip(3);
+        assert_eq!(cv.position().index, 3);
+        cv.skip(usize::MAX);
+        assert_eq!(cv.position().index, (3 + usize::MAX % 5) % 5);
+
+        cv.reset();
+        cv.rewind(1);
+        assert_eq!(cv.peek(), &4);
+        cv.rewind(12);
+        assert_eq!(cv.peek(), &2);
+        cv.rewind(usize::MAX);
+        assert_eq!(cv.position().index, (2 + 5 - usize::MAX % 5) % 5);
+    }
+
+    #[test]
+    fn advance_by_signed() {
+        let mut cv = CircularVec::new(0, 1..5);
+        cv.advance_by(7);
+        assert_eq!(cv.peek(), &2);
+        cv.advance_by(-3);
+        assert_eq!(cv.peek(), &4);
+        cv.advance_by(0);
+        assert_eq!(cv.peek(), &4);
+        cv.advance_by(isize::MIN);
+        assert_eq!(
+            cv.position().index,
+            (4 + 5 - isize::MIN.unsigned_abs() % 5) % 5
+        );
+    }
+
+    #[test]
+    fn insert_keeps_cursor() {
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        cv.next();
+        cv.insert(0, 0);
+        assert_eq!(cv.peek(), &2);
+        cv.insert(2, 10);
+        assert_eq!(cv.peek(), &2);
+        cv.insert_at_cursor(20);
+        assert_eq!(cv.next(), &20);
+        assert_eq!(cv.next(), &2);
+        cv.push(4);
+        assert_eq!(cv.next(), &3);
+        assert_eq!(cv.next(), &4);
+        assert_eq!(cv.next(), &0);
+    }
+
+    #[test]
+    fn remove_keeps_cursor() {
+        let mut cv = CircularVec::new(1, vec![2, 3, 4]);
+        cv.skip(2);
+        assert_eq!(cv.remove(0), Ok(1));
+        assert_eq!(cv.peek(), &3);
+        assert_eq!(cv.remove_at_cursor(), Ok(3));
+        assert_eq!(cv.peek(), &4);
+        assert_eq!(cv.remove_at_cursor(), Ok(4));
+        assert_eq!(cv.peek(), &2);
+        assert_eq!(cv.remove(0), Err(CircularVecError::Empty));
+        assert_eq!(cv.peek(), &2);
+    }
+
+    #[test]
+    fn swap_remove_keeps_cursor() {
+        let mut cv = CircularVec::new(1, vec![2, 3, 4, 5]);
+        cv.skip(4);
+        assert_eq!(cv.swap_remove(1), Ok(2));
+        assert_eq!(cv.peek(), &5);
+
+        let mut cv = CircularVec::new(1, vec![2, 3, 4]);
+        cv.skip(2);
+        assert_eq!(cv.swap_remove(2), Ok(3));
+        assert_eq!(cv.next(), &4);
+        assert_eq!(cv.next(), &1);
+
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        cv.skip(2);
+        assert_eq!(cv.swap_remove(2), Ok(3));
+        assert_eq!(cv.peek(), &1);
+    }
+
+    #[test]
+    fn truncate_and_retain() {
+        let mut cv = CircularVec::new(1, 2..=6);
+        cv.skip(4);
+        assert_eq!(cv.truncate(0), Err(CircularVecError::Empty));
+        assert_eq!(cv.truncate(4), Ok(()));
+        assert_eq!(cv.peek(), &1);
+
+        cv.skip(1);
+        assert_eq!(cv.retain(|&x| x != 2), Ok(()));
+        assert_eq!(cv.next(), &3);
+        assert_eq!(cv.next(), &4);
+        assert_eq!(cv.retain(|_| false), Err(CircularVecError::Empty));
+        assert_eq!(cv.retain(|&x| x < 4), Ok(()));
+        assert_eq!(cv.next(), &1);
+    }
+
+    #[test]
+    fn equality_includes_cursor() {
+        let mut a = CircularVec::from([1, 2, 3]);
+        let mut b = a.clone();
+        assert_eq!(a, b);
+        a.next();
+        assert_ne!(a, b);
+        assert!(a > b);
+        assert_eq!(a.as_ref(), b.as_ref());
+        b.next();
+        assert_eq!(a, b);
+        assert!(CircularVec::from([1, 2]) < CircularVec::from([1, 3]));
+
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |cv: &CircularVec<i32>| {
+            let mut h = DefaultHasher::new();
+            cv.hash(&mut h);
+            h.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+    }
+
+    #[test]
+    fn conversions() {
+        let boxed: Box<[u8]> = vec![1, 2].into_boxed_slice();
+        let mut cv = CircularVec::try_from(boxed).unwrap();
+        assert_eq!(
+            CircularVec::<u8>::try_from(Vec::new().into_boxed_slice()).err(),
+            Some(CircularVecError::Empty)
+        );
+
+        cv.extend(vec![3, 4]);
+        cv.extend(&[5]);
+        cv[0] = 10;
+        assert_eq!(cv.len(), 5);
+        assert!(cv.contains(&5));
+        assert_eq!(cv.next(), &10);
+        assert_eq!(Vec::from(cv), vec![10, 2, 3, 4, 5]);
+
+        let mut default: CircularVec<String> = Default::default();
+        assert_eq!(default.next(), "");
+        assert_eq!(
+            format!("{:?}", CircularVec::from([7])),
+            "CircularVec { items: [7], index: 0, direction: Forward, mode: Wrap, stride: 1, enabled: [true], laps: 0 }"
+        );
+    }
+
+    #[test]
+    fn bounded_iteration() {
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        cv.next();
+        assert_eq!(cv.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(cv.lap().copied().collect::<Vec<_>>(), [2, 3, 1]);
+        assert_eq!(cv.lap().rev().copied().collect::<Vec<_>>(), [1, 3, 2]);
+        assert_eq!(cv.laps(2).copied().collect::<Vec<_>>(), [2, 3, 1, 2, 3, 1]);
+        assert_eq!(cv.laps(0).count(), 0);
+        assert_eq!(
+            cv.cycle().take(4).copied().collect::<Vec<_>>(),
+            [2, 3, 1, 2]
+        );
+        assert_eq!(cv.position().index, 1);
+
+        let mut sum = 0;
+        for x in &cv {
+            sum += x;
+        }
+        assert_eq!(sum, 6);
+
+        for x in cv.iter_mut() {
+            *x *= 10;
+        }
+        assert_eq!(cv.peek(), &20);
+    }
+
+    #[test]
+    fn advancing_lap() {
+        let mut cv = CircularVec::new('a', vec!['b', 'c']);
+        cv.next();
+        assert_eq!(cv.advance_lap().collect::<String>(), "bca");
+        assert_eq!(cv.position().index, 1);
+        assert_eq!(cv.advance_lap().take(2).collect::<String>(), "bc");
+        assert_eq!(cv.next(), &'a');
+    }
+
+    #[test]
+    fn skip_disabled() {
+        let mut cv = CircularVec::new(1, vec![2, 3, 4]);
+        cv.disable(1);
+        cv.disable(2);
+        assert!(!cv.is_enabled(1));
+        assert_eq!(cv.next(), &1);
+        assert_eq!(cv.next(), &4);
+        assert_eq!(cv.next(), &1);
+        *cv.next_mut() += 10;
+        assert_eq!(cv[3], 14);
+
+        cv.enable(2);
+        assert_eq!(cv.next(), &1);
+        assert_eq!(cv.next(), &3);
+        assert_eq!(cv.position().index, 3);
+        assert_eq!(cv, CircularVec::from_parts(vec![1, 2, 3, 14], 3));
+    }
+
+    #[test]
+    fn all_disabled() {
+        let mut cv = CircularVec::new('a', vec!['b']);
+        cv.next();
+        cv.disable(0);
+        cv.disable(1);
+        assert_eq!(cv.try_next(), Err(CircularVecError::AllDisabled));
+        assert_eq!(cv.try_next_mut(), Err(CircularVecError::AllDisabled));
+        assert_eq!(cv.try_next_with_wrap(), Err(CircularVecError::AllDisabled));
+        assert_eq!(cv.next_where(|_| true), None);
+        assert_eq!(cv.position().index, 1);
+
+        cv.push('c');
+        assert_eq!(cv.try_next_with_wrap(), Ok((&'c', true)));
+        *cv.try_next_mut().unwrap() = 'd';
+        assert_eq!(cv.try_next(), Ok(&'d'));
+    }
+
+    #[test]
+    #[should_panic(expected = "every item is disabled")]
+    fn next_panics_when_all_disabled() {
+        let mut cv = CircularVec::new(1, None);
+        cv.disable(0);
+        cv.next();
+    }
+
+    #[test]
+    fn next_where_does_one_lap() {
+        let mut cv = CircularVec::new(1, 2..=6);
+        cv.disable(3);
+        let mut calls = 0;
+        assert_eq!(
+            cv.next_where(|&x| {
+                calls += 1;
+                x % 2 == 0
+            }),
+            Some(&2)
+        );
+        assert_eq!(calls, 2);
+        assert_eq!(cv.next_where(|&x| x % 2 == 0), Some(&6));
+        let mut calls = 0;
+        assert_eq!(
+            cv.next_where(|&x| {
+                calls += 1;
+                x > 10
+            }),
+            None
+        );
+        assert_eq!(calls, 5);
+    }
+
+    #[test]
+    fn flags_follow_items() {
+        let mut cv = CircularVec::new(1, vec![2, 3, 4]);
+        cv.disable(1);
+        cv.insert(0, 0);
+        assert!(cv.is_enabled(0));
+        assert!(!cv.is_enabled(2));
+        cv.remove(0).unwrap();
+        assert!(!cv.is_enabled(1));
+        cv.swap_remove(0).unwrap();
+        assert!(cv.is_enabled(0));
+        assert!(!cv.is_enabled(1));
+        cv.retain(|&x| x == 2).unwrap();
+        assert_eq!(cv.try_next(), Err(CircularVecError::AllDisabled));
+        cv.extend(vec![5]);
+        assert_eq!(cv.next(), &5);
+    }
+
+    #[test]
+    fn count_laps() {
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        assert_eq!(cv.next_with_wrap(), (&1, false));
+        assert_eq!(cv.next_with_wrap(), (&2, false));
+        assert_eq!(cv.next_with_wrap(), (&3, true));
+        assert_eq!(cv.laps_completed(), 1);
+
+        cv.prev();
+        cv.rewind(5);
+        assert_eq!(cv.laps_completed(), 1);
+        cv.skip(2);
+        assert_eq!(cv.laps_completed(), 1);
+        cv.skip(7);
+        assert_eq!(cv.laps_completed(), 4);
+
+        cv.disable(0);
+        cv.set_position(2);
+        assert_eq!(cv.next_with_wrap(), (&3, true));
+        assert_eq!(cv.next_with_wrap(), (&2, false));
+        assert_eq!(cv.next_where(|&x| x == 2), Some(&2));
+        assert_eq!(cv.laps_completed(), 6);
+
+        cv.enable(0);
+        assert_eq!(cv.advance_lap().count(), 3);
+        assert_eq!(cv.laps_completed(), 7);
+
+        let mut single = CircularVec::new('x', None);
+        assert_eq!(single.next_with_wrap(), (&'x', true));
+    }
+
+    #[test]
+    fn wrap_hooks() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut cv = CircularVec::new(1, vec![2]);
+        let hook_seen = Arc::clone(&seen);
+        cv.on_wrap(move |laps| hook_seen.lock().unwrap().push(laps));
+
+        cv.next();
+        assert!(seen.lock().unwrap().is_empty());
+        cv.next();
+        cv.skip(5);
+        assert_eq!(*seen.lock().unwrap(), [1, 3]);
+
+        let mut clone = cv.clone();
+        assert_eq!(clone.laps_completed(), 3);
+        clone.skip(2);
+        assert_eq!(*seen.lock().unwrap(), [1, 3]);
+
+        cv.clear_wrap_hooks();
+        cv.skip(2);
+        assert_eq!(*seen.lock().unwrap(), [1, 3]);
+    }
+
+    fn sweep(cv: &mut CircularVec<u8>, n: usize) -> Vec<u8> {
+        (0..n).map(|_| *cv.next()).collect()
+    }
+
+    #[test]
+    fn bounce() {
+        let mut cv = CircularVec::new(0, 1..4).with_traversal_mode(TraversalMode::Bounce);
+        assert_eq!(sweep(&mut cv, 10), [0, 1, 2, 3, 2, 1, 0, 1, 2, 3]);
+        assert_eq!(
+            cv.position(),
+            Position {
+                index: 2,
+                direction: Direction::Backward
+            }
+        );
+        assert_eq!(cv.peek_back(), &3);
+        assert_eq!(cv.prev(), &3);
+        assert_eq!(cv.prev(), &2);
+        assert_eq!(cv.position().direction, Direction::Forward);
+
+        cv.set_traversal_mode(TraversalMode::BounceRepeatEnds);
+        assert_eq!(sweep(&mut cv, 10), [2, 3, 3, 2, 1, 0, 0, 1, 2, 3]);
+        cv.set_traversal_mode(TraversalMode::Wrap);
+        assert_eq!(cv.position().direction, Direction::Forward);
+        assert_eq!(sweep(&mut cv, 3), [3, 0, 1]);
+
+        let mut single = CircularVec::new(7, None).with_traversal_mode(TraversalMode::Bounce);
+        assert_eq!(sweep(&mut single, 2), [7, 7]);
+    }
+
+    #[test]
+    fn bounce_moves_and_laps() {
+        let mut cv = CircularVec::new(0, 1..4).with_traversal_mode(TraversalMode::Bounce);
+        cv.skip(4);
+        assert_eq!(cv.peek(), &2);
+        assert_eq!(cv.laps_completed(), 0);
+        cv.skip(2);
+        assert_eq!(cv.laps_completed(), 1);
+        cv.skip(13);
+        assert_eq!(cv.laps_completed(), 3);
+        assert_eq!(cv.position().index, 1);
+        cv.rewind(13);
+        assert_eq!(cv.position().index, 0);
+        cv.advance_by(-1);
+        assert_eq!(cv.peek(), &1);
+
+        cv.reset();
+        assert_eq!(cv.advance_lap().copied().collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(cv.lap().copied().collect::<Vec<_>>(), [2, 3, 0, 1]);
+        assert_eq!(cv.advance_lap().copied().collect::<Vec<_>>(), [2, 1, 0, 1]);
+        assert_eq!(cv.laps_completed(), 4);
+    }
+
+    #[test]
+    fn bounce_skips_disabled() {
+        let mut cv = CircularVec::new(0, 1..5).with_traversal_mode(TraversalMode::Bounce);
+        cv.skip(2);
+        cv.disable(3);
+        cv.disable(4);
+        assert_eq!(sweep(&mut cv, 4), [2, 2, 1, 0]);
+
+        cv.set_position(3);
+        let mut seen = Vec::new();
+        assert_eq!(
+            cv.next_where(|&x| {
+                seen.push(x);
+                false
+            }),
+            None
+        );
+        seen.sort();
+        assert_eq!(seen, [0, 1, 2]);
+    }
+
+    #[test]
+    fn bounce_direction_survives_edits() {
+        let mut cv = CircularVec::new(0, 1..4).with_traversal_mode(TraversalMode::Bounce);
+        cv.skip(4);
+        cv.push(4);
+        assert_eq!(cv.position().direction, Direction::Backward);
+        assert_eq!(cv.remove(0), Ok(0));
+        assert_eq!(cv.remove(0), Ok(1));
+        assert_eq!(
+            cv.position(),
+            Position {
+                index: 0,
+                direction: Direction::Forward
+            }
+        );
+        assert_eq!(sweep(&mut cv, 4), [2, 3, 4, 3]);
+
+        // Mid way through a backward sweep, the item before the cursor
+        // is the one that follows it.
+        let mut cv = CircularVec::new(0, 1..4).with_traversal_mode(TraversalMode::Bounce);
+        cv.skip(4);
+        let mut removed = cv.clone();
+        assert_eq!(removed.remove_at_cursor(), Ok(2));
+        assert_eq!(sweep(&mut removed, 3), [1, 0, 1]);
+        let mut swapped = cv.clone();
+        assert_eq!(swapped.swap_remove(2), Ok(2));
+        assert_eq!(sweep(&mut swapped, 3), [1, 0, 1]);
+        let mut retained = cv.clone();
+        retained.retain(|&x| x != 1 && x != 2).unwrap();
+        assert_eq!(sweep(&mut retained, 3), [0, 3, 0]);
+        let mut inserted = cv.clone();
+        inserted.insert_at_cursor(9);
+        assert_eq!(inserted.position().direction, Direction::Backward);
+        assert_eq!(sweep(&mut inserted, 4), [9, 2, 1, 0]);
+
+        let mut a = CircularVec::from([1, 2, 3]).with_traversal_mode(TraversalMode::Bounce);
+        let mut b = a.clone();
+        a.skip(1);
+        b.skip(3);
+        assert_eq!(a.position().index, b.position().index);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn stride() {
+        let mut cv = CircularVec::new(0, 1..5).with_stride(3);
+        assert_eq!(sweep(&mut cv, 6), [0, 3, 1, 4, 2, 0]);
+        assert_eq!(cv.laps_completed(), 3);
+        assert_eq!(cv.peek_back(), &0);
+        assert_eq!(cv.prev(), &0);
+        cv.skip(2);
+        assert_eq!(cv.peek(), &1);
+        cv.rewind(4);
+        assert_eq!(cv.peek(), &4);
+        assert_eq!(cv.set_stride(0), Err(CircularVecError::ZeroStride));
+        assert_eq!(cv.stride(), 3);
+
+        cv.set_stride(-2).unwrap();
+        cv.reset();
+        assert_eq!(sweep(&mut cv, 6), [0, 3, 1, 4, 2, 0]);
+        assert_eq!(cv.laps_completed(), 6);
+
+        let mut cv = CircularVec::new(0, 1..4)
+            .with_traversal_mode(TraversalMode::Bounce)
+            .with_stride(2);
+        assert_eq!(sweep(&mut cv, 4), [0, 2, 2, 0]);
+        assert!(!cv.visits_every_item());
+    }
+
+    #[test]
+    fn first_visits_skip_repeats() {
+        let modes = [
+            TraversalMode::Wrap,
+            TraversalMode::Bounce,
+            TraversalMode::BounceRepeatEnds,
+        ];
+        for &mode in &modes {
+            for len in 1..7 {
+                for stride in (-9..10).filter(|&s| s != 0) {
+                    for start in 0..mode.period(len) {
+                        let mut cursor = CursorState {
+                            mode,
+                            stride,
+                            ..CursorState::default()
+                        };
+                        cursor.set_phase(start, len);
+                        let mut seen = vec![false; len];
+                        let expected: Vec<(usize, usize)> = (0..cursor.orbit_len(len))
+                            .map(|calls| (calls, cursor.index_after(calls, false, len)))
+                            .filter(|&(_, index)| !mem::replace(&mut seen[index], true))
+                            .collect();
+                        let actual: Vec<_> = cursor.first_visits(len).collect();
+                        assert_eq!(actual, expected, "{:?} {} {} {}", mode, len, stride, start);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn stride_coverage() {
+        let mut cv = CircularVec::new(0, 1..6).with_stride(4);
+        assert_eq!(cv.reachable_items(), 3);
+        assert!(!cv.visits_every_item());
+        cv.skip(1);
+        cv.disable(0);
+        assert_eq!(sweep(&mut cv, 3), [4, 2, 4]);
+
+        let stride = cv.coprime_stride(4);
+        assert_eq!(stride, 5);
+        cv.set_stride(stride).unwrap();
+        assert!(cv.visits_every_item());
+        assert_eq!(cv.coprime_stride(-3), -5);
+        assert_eq!(cv.coprime_stride(0), 1);
+
+        // The sign survives the extremes. On 64-bit targets 7 divides
+        // `isize::MAX`, so the search has to head back towards zero.
+        let mut seven = CircularVec::new(0, 1..7);
+        let up = seven.coprime_stride(isize::MAX);
+        let down = seven.coprime_stride(isize::MIN);
+        assert!(up > 0 && down < 0);
+        for stride in [up, down] {
+            seven.set_stride(stride).unwrap();
+            assert!(seven.visits_every_item());
+        }
+        if cfg!(target_pointer_width = "64") {
+            assert_eq!((up, down), (isize::MAX - 1, isize::MIN + 2));
+        }
+        let four = CircularVec::new(0, 1..4);
+        assert_eq!(four.coprime_stride(isize::MIN), -isize::MAX);
+        assert_eq!(four.coprime_stride(isize::MAX), isize::MAX);
+
+        cv.set_traversal_mode(TraversalMode::BounceRepeatEnds);
+        assert_eq!(cv.coprime_stride(2), 5);
+        cv.set_stride(2).unwrap();
+        assert!(cv.visits_every_item());
+    }
+
+    #[test]
+    fn rotate_keeps_cursor() {
+        let mut cv = CircularVec::new(0, 1..5);
+        cv.skip(3);
+        cv.disable(4);
+        cv.rotate_left(1);
+        assert_eq!(cv.as_ref(), [1, 2, 3, 4, 0]);
+        assert_eq!(cv.peek(), &3);
+        assert!(!cv.is_enabled(3));
+        cv.rotate_right(7);
+        assert_eq!(cv.as_ref(), [4, 0, 1, 2, 3]);
+        assert_eq!(cv.position().index, 4);
+        cv.rotate_left(5);
+        assert_eq!(cv.as_ref(), [4, 0, 1, 2, 3]);
+        assert_eq!(sweep(&mut cv, 3), [3, 0, 1]);
+
+        cv.rotate_to_cursor();
+        assert_eq!(cv.as_ref(), [2, 3, 4, 0, 1]);
+        assert_eq!(cv.position().index, 0);
+        assert_eq!(sweep(&mut cv, 4), [2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn wrapping_access() {
+        let mut cv = CircularVec::new(10, vec![20, 30, 40]);
+        assert_eq!(cv.get_wrapping(-1), &40);
+        assert_eq!(cv.get_wrapping(6), &30);
+        assert_eq!(cv.get_wrapping(isize::MIN), &10);
+        assert_eq!(cv[Wrapping(-5)], 40);
+        cv[Wrapping(-4)] += 1;
+        *cv.get_wrapping_mut(9) += 1;
+        assert_eq!(cv.as_ref(), [11, 21, 30, 40]);
+
+        cv.skip(3);
+        assert_eq!(cv.get_relative(0), &40);
+        assert_eq!(cv.get_relative(1), &11);
+        assert_eq!(cv.get_relative(-2), &21);
+        *cv.get_relative_mut(-7) += 1;
+        assert_eq!(cv[0], 12);
+        assert_eq!(cv[1..3], [21, 30]);
+    }
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CircularVec<u8>>();
+    }
+
+    #[test]
+    fn new_with_rest() {
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        assert_eq!(cv.next(), &1);
+        assert_eq!(cv.next(), &2);
+        assert_eq!(cv.next(), &3);
+        assert_eq!(cv.next(), &1);
+
+        let mut single = CircularVec::new("only", None);
+        assert_eq!(single.next(), &"only");
+        assert_eq!(single.next(), &"only");
+    }
+
+    #[test]
+    fn reject_empty() {
+        assert_eq!(
+            CircularVec::<u8>::try_from(Vec::new()).err(),
+            Some(CircularVecError::Empty)
+        );
+        assert_eq!(
+            CircularVec::<u8>::try_from_iter(core::iter::empty()).err(),
+            Some(CircularVecError::Empty)
+        );
+        assert!(CircularVec::try_from(vec![1]).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one item")]
+    fn collect_empty_panics() {
+        let _: CircularVec<u8> = Vec::new().into_iter().collect();
+    }
+}
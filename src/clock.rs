@@ -0,0 +1,69 @@
+//! Time sources for the time-driven collections in this crate.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+///
+/// `SystemClock` reads the real monotonic clock. Tests can use a
+/// `ManualClock` instead, so that time only moves when they say so.
+pub trait Clock {
+    /// The current time.
+    fn now(&self) -> Instant;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+/// The real clock, read with `Instant::now`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so
+/// a test can keep one clone and hand another to the collection.
+///
+///     # use circular_vec::{Clock, ManualClock};
+///     # use std::time::{Duration, Instant};
+///     let start = Instant::now();
+///     let clock = ManualClock::new(start);
+///     let handle = clock.clone();
+///     handle.advance(Duration::from_secs(5));
+///     assert_eq!(clock.now(), start + Duration::from_secs(5));
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    /// Create a ManualClock that reads `start` until it is moved.
+    pub fn new(start: Instant) -> Self {
+        ManualClock {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    /// Move the clock forwards by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+
+    /// Set the clock to `now`, which may be earlier than its current time.
+    pub fn set(&self, now: Instant) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
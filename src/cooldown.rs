@@ -0,0 +1,373 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+use crate::CircularVec;
+
+/// A circular collection whose items can be put in cooldown or rate
+/// limited, for rotating through credentials such as API keys that each
+/// have their own limits.
+///
+/// `next_available` works like `CircularVec::next_where`, passing over
+/// items that are still in cooldown or have run out of tokens. When
+/// every item is unavailable, `time_until_next_available` says how long
+/// to sleep before one comes back.
+///
+/// The clock is a type parameter so tests can use a `ManualClock`.
+///
+///     # use circular_vec::{CircularVec, CooldownCircularVec, ManualClock};
+///     # use std::time::{Duration, Instant};
+///     let clock = ManualClock::new(Instant::now());
+///     let keys = CircularVec::from(["key-a", "key-b"]);
+///     let mut keys = CooldownCircularVec::with_clock(keys, clock.clone());
+///
+///     // key-a was rate limited and told to retry in a minute.
+///     keys.mark_cooldown_for(0, Duration::from_secs(60));
+///     assert_eq!(keys.next_available_now(), Some((1, &"key-b")));
+///     keys.mark_cooldown_for(1, Duration::from_secs(90));
+///
+///     assert_eq!(keys.next_available_now(), None);
+///     assert_eq!(keys.time_until_next_available(), Some(Duration::from_secs(60)));
+///     clock.advance(Duration::from_secs(60));
+///     assert_eq!(keys.next_available_now(), Some((0, &"key-a")));
+pub struct CooldownCircularVec<T, C = SystemClock> {
+    items: CircularVec<T>,
+    cooldowns: Vec<Option<Instant>>,
+    buckets: Vec<Option<TokenBucket>>,
+    clock: C,
+}
+
+/// A token bucket for one item: up to `capacity` uses in a burst, with
+/// one use coming back every `refill_every`.
+#[derive(Clone, Copy, Debug)]
+struct TokenBucket {
+    capacity: u32,
+    refill_every: Duration,
+    tokens: u32,
+    // When `tokens` was last brought up to date. Partial progress
+    // towards the next token is kept by only moving this on by whole
+    // refill periods.
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    /// The number of whole refill periods between `refilled` and `now`.
+    fn periods(&self, now: Instant) -> u128 {
+        now.saturating_duration_since(self.refilled).as_nanos() / self.refill_every.as_nanos()
+    }
+
+    fn tokens_at(&self, now: Instant) -> u32 {
+        let tokens = u128::from(self.tokens) + self.periods(now);
+        tokens.min(u128::from(self.capacity)) as u32
+    }
+
+    fn wait_at(&self, now: Instant) -> Duration {
+        if self.tokens_at(now) > 0 {
+            return Duration::ZERO;
+        }
+        // With no tokens, less than one refill period has passed.
+        self.refill_every - now.saturating_duration_since(self.refilled)
+    }
+
+    fn take(&mut self, now: Instant) {
+        let periods = self.periods(now);
+        let tokens = self.tokens_at(now);
+        if tokens == self.capacity {
+            // A full bucket gains nothing from waiting, so the next
+            // token is a whole period away.
+            self.refilled = now;
+        } else {
+            // Below capacity, so `periods` is less than `capacity`.
+            self.refilled += self.refill_every * periods as u32;
+        }
+        self.tokens = tokens - 1;
+    }
+}
+
+impl<T> CooldownCircularVec<T, SystemClock> {
+    /// Wrap `items`, timed by the system clock. No items start in
+    /// cooldown or rate limited.
+    pub fn new(items: CircularVec<T>) -> Self {
+        Self::with_clock(items, SystemClock)
+    }
+}
+
+impl<T, C> CooldownCircularVec<T, C> {
+    /// Wrap `items`, timed by `clock`. No items start in cooldown or rate
+    /// limited.
+    pub fn with_clock(items: CircularVec<T>, clock: C) -> Self {
+        CooldownCircularVec {
+            cooldowns: vec![None; items.len()],
+            buckets: vec![None; items.len()],
+            items,
+            clock,
+        }
+    }
+
+    /// Get the next item that is available at `now`, along with its
+    /// index, skipping items that are disabled, in cooldown or out of
+    /// tokens. Returns `None` without moving the cursor if no item is
+    /// available. Takes a token from the item's bucket, if it has one.
+    pub fn next_available(&mut self, now: Instant) -> Option<(usize, &T)> {
+        let (steps, index) = self.find_available(now)?;
+        self.items.skip(steps + 1);
+        if let Some(bucket) = &mut self.buckets[index] {
+            bucket.take(now);
+        }
+        Some((index, &self.items[index]))
+    }
+
+    /// Put the item at `index` in cooldown until `until`, replacing any
+    /// cooldown it already had. `next_available` passes over it until
+    /// then.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn mark_cooldown(&mut self, index: usize, until: Instant) {
+        self.cooldowns[index] = Some(until);
+    }
+
+    /// Take the item at `index` out of cooldown straight away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn clear_cooldown(&mut self, index: usize) {
+        self.cooldowns[index] = None;
+    }
+
+    /// The time set by the last `mark_cooldown` for the item at `index`,
+    /// which may already have passed, or `None` if it has none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn cooldown_until(&self, index: usize) -> Option<Instant> {
+        self.cooldowns[index]
+    }
+
+    /// Remove the token bucket from the item at `index`, so only its
+    /// cooldown limits it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn clear_token_bucket(&mut self, index: usize) {
+        self.buckets[index] = None;
+    }
+
+    /// Whether the item at `index` would be available to `next_available`
+    /// at `now`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn is_available_at(&self, index: usize, now: Instant) -> bool {
+        self.items.is_enabled(index) && self.wait_at(index, now) == Duration::ZERO
+    }
+
+    /// How long after `now` until `next_available` has an item to return:
+    /// zero if it has one already, or `None` if it never will because
+    /// every item it can reach is disabled.
+    pub fn time_until_available_at(&self, now: Instant) -> Option<Duration> {
+        let mut shortest = None;
+        self.items.find_forward(|i| {
+            if self.items.is_enabled(i) {
+                let wait = self.wait_at(i, now);
+                shortest = Some(shortest.map_or(wait, |s: Duration| s.min(wait)));
+            }
+            shortest == Some(Duration::ZERO)
+        });
+        shortest
+    }
+
+    /// How long the item at `index` has to wait after `now` for both its
+    /// cooldown to end and a token, ignoring whether it is disabled.
+    fn wait_at(&self, index: usize, now: Instant) -> Duration {
+        let cooldown = self.cooldowns[index]
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(now));
+        let bucket = self.buckets[index].map_or(Duration::ZERO, |b| b.wait_at(now));
+        cooldown.max(bucket)
+    }
+
+    /// The number of steps to and the index of the next item available at
+    /// `now`, in the order `next` would visit them.
+    fn find_available(&self, now: Instant) -> Option<(usize, usize)> {
+        self.items.find_forward(|i| self.is_available_at(i, now))
+    }
+
+    /// The wrapped CircularVec, for example to look at the cursor.
+    pub fn get_ref(&self) -> &CircularVec<T> {
+        &self.items
+    }
+
+    /// Unwrap the CircularVec, dropping the cooldowns and token buckets.
+    pub fn into_inner(self) -> CircularVec<T> {
+        self.items
+    }
+}
+
+impl<T, C: Clock> CooldownCircularVec<T, C> {
+    /// `next_available` at the clock's current time.
+    pub fn next_available_now(&mut self) -> Option<(usize, &T)> {
+        let now = self.clock.now();
+        self.next_available(now)
+    }
+
+    /// Put the item at `index` in cooldown for `duration` from now, such
+    /// as the delay from a `Retry-After` header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn mark_cooldown_for(&mut self, index: usize, duration: Duration) {
+        let until = self.clock.now() + duration;
+        self.mark_cooldown(index, until);
+    }
+
+    /// Limit the item at `index` with a token bucket that allows up to
+    /// `capacity` uses in a burst and gives one use back every
+    /// `refill_every`. The bucket starts full, and replaces any bucket the
+    /// item already had. Cooldowns still apply on top of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, or if `capacity` or
+    /// `refill_every` is zero.
+    pub fn set_token_bucket(&mut self, index: usize, capacity: u32, refill_every: Duration) {
+        assert!(capacity > 0, "token bucket capacity must not be zero");
+        assert!(
+            refill_every > Duration::ZERO,
+            "token bucket refill interval must not be zero"
+        );
+        self.buckets[index] = Some(TokenBucket {
+            capacity,
+            refill_every,
+            tokens: capacity,
+            refilled: self.clock.now(),
+        });
+    }
+
+    /// Whether the item at `index` is available to `next_available` now.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn is_available(&self, index: usize) -> bool {
+        self.is_available_at(index, self.clock.now())
+    }
+
+    /// How long until `next_available_now` has an item to return: zero
+    /// if it has one already, or `None` if it never will because every
+    /// item it can reach is disabled. When every item is in cooldown or
+    /// out of tokens, sleeping for this long is exactly enough.
+    pub fn time_until_next_available(&self) -> Option<Duration> {
+        self.time_until_available_at(self.clock.now())
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for CooldownCircularVec<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CooldownCircularVec")
+            .field("items", &self.items)
+            .field("cooldowns", &self.cooldowns)
+            .field("buckets", &self.buckets)
+            .finish()
+    }
+}
+
+impl<T, C> Deref for CooldownCircularVec<T, C> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    const SEC: Duration = Duration::from_secs(1);
+
+    /// Three keys, and the clock that times them.
+    fn keys() -> (ManualClock, CooldownCircularVec<char, ManualClock>) {
+        let clock = ManualClock::new(Instant::now());
+        let cv = CooldownCircularVec::with_clock(CircularVec::from(['a', 'b', 'c']), clock.clone());
+        (clock, cv)
+    }
+
+    fn take(cv: &mut CooldownCircularVec<char, ManualClock>, n: usize) -> Vec<Option<char>> {
+        (0..n)
+            .map(|_| cv.next_available_now().map(|(_, &c)| c))
+            .collect()
+    }
+
+    #[test]
+    fn cooldowns_are_skipped_until_they_end() {
+        let (clock, mut cv) = keys();
+        cv.mark_cooldown_for(1, 10 * SEC);
+        assert_eq!(take(&mut cv, 3), [Some('a'), Some('c'), Some('a')]);
+        assert!(!cv.is_available(1));
+        assert_eq!(cv.time_until_next_available(), Some(Duration::ZERO));
+
+        cv.mark_cooldown_for(0, 5 * SEC);
+        cv.mark_cooldown_for(2, 20 * SEC);
+        assert_eq!(cv.next_available_now(), None);
+        assert_eq!(cv.time_until_next_available(), Some(5 * SEC));
+        clock.advance(5 * SEC);
+        assert_eq!(cv.next_available_now(), Some((0, &'a')));
+
+        cv.clear_cooldown(2);
+        assert_eq!(cv.cooldown_until(2), None);
+        assert_eq!(take(&mut cv, 2), [Some('c'), Some('a')]);
+    }
+
+    #[test]
+    fn token_buckets_refill() {
+        let (clock, mut cv) = keys();
+        cv.set_token_bucket(0, 2, 10 * SEC);
+        cv.mark_cooldown_for(1, 600 * SEC);
+        cv.mark_cooldown_for(2, 600 * SEC);
+        assert_eq!(take(&mut cv, 3), [Some('a'), Some('a'), None]);
+        assert_eq!(cv.time_until_next_available(), Some(10 * SEC));
+
+        clock.advance(4 * SEC);
+        assert_eq!(cv.time_until_next_available(), Some(6 * SEC));
+        clock.advance(11 * SEC);
+        // One token has come back, and the next is 5 seconds away.
+        assert_eq!(take(&mut cv, 2), [Some('a'), None]);
+        assert_eq!(cv.time_until_next_available(), Some(5 * SEC));
+
+        // A full bucket stops filling, so after a long wait only the
+        // capacity is available and the next token is a whole period away.
+        clock.advance(60 * SEC);
+        assert_eq!(take(&mut cv, 3), [Some('a'), Some('a'), None]);
+        assert_eq!(cv.time_until_next_available(), Some(10 * SEC));
+
+        cv.clear_token_bucket(0);
+        assert_eq!(take(&mut cv, 2), [Some('a'), Some('a')]);
+    }
+
+    #[test]
+    fn never_available_when_all_disabled() {
+        let clock = ManualClock::new(Instant::now());
+        let mut items = CircularVec::from(['a', 'b']);
+        items.disable(0);
+        items.disable(1);
+        let mut cv = CooldownCircularVec::with_clock(items, clock);
+        assert_eq!(cv.next_available_now(), None);
+        assert_eq!(cv.time_until_next_available(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must not be zero")]
+    fn reject_empty_bucket() {
+        keys().1.set_token_bucket(0, 0, SEC);
+    }
+}
@@ -0,0 +1,203 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Deref;
+
+use crate::iter::Lap;
+use crate::wrap;
+use crate::{CircularVec, CircularVecError};
+
+/// A cheap handle with its own round-robin position over items that are
+/// shared with other cursors.
+///
+/// The items live in an `Arc<[T]>` and are never copied: cloning a Cursor,
+/// or making another with `at`, only bumps the reference count. Each
+/// Cursor moves independently, so many consumers can take turns over the
+/// same list from different starting offsets.
+///
+///     # use circular_vec::Cursor;
+///     # use std::sync::Arc;
+///     let mirrors: Arc<[&str]> = Arc::from(vec!["eu", "us", "ap"]);
+///     let mut first = Cursor::new(Arc::clone(&mirrors)).unwrap();
+///     let mut second = first.at(1);
+///
+///     assert_eq!(first.next(), &"eu");
+///     assert_eq!(second.next(), &"us");
+///     assert_eq!(first.next(), &"us");
+///     assert_eq!(second.next(), &"ap");
+pub struct Cursor<T> {
+    items: Arc<[T]>,
+    index: usize,
+}
+
+impl<T> Cursor<T> {
+    /// Create a Cursor over `items` starting at the first item. Returns
+    /// `CircularVecError::Empty` if there are no items.
+    pub fn new(items: Arc<[T]>) -> Result<Self, CircularVecError> {
+        Self::starting_at(items, 0)
+    }
+
+    /// Create a Cursor over `items` so that `next` first returns the item
+    /// at `offset`, wrapping around if it is past the end. Returns
+    /// `CircularVecError::Empty` if there are no items.
+    pub fn starting_at(items: Arc<[T]>, offset: usize) -> Result<Self, CircularVecError> {
+        if items.is_empty() {
+            return Err(CircularVecError::Empty);
+        }
+        let index = offset % items.len();
+        Ok(Cursor { items, index })
+    }
+
+    /// Another Cursor over the same items, starting at `offset`. The items
+    /// are shared, not copied.
+    pub fn at(&self, offset: usize) -> Self {
+        Cursor {
+            items: Arc::clone(&self.items),
+            index: offset % self.items.len(),
+        }
+    }
+
+    /// Get an immutable reference to the next item, moving this cursor on.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &T {
+        let original_index = self.index;
+        self.index = wrap::increment(self.index, self.items.len());
+        &self.items[original_index]
+    }
+
+    /// Step this cursor backwards and get an immutable reference to the
+    /// item it now points at. This is the inverse of `next`.
+    pub fn prev(&mut self) -> &T {
+        self.index = wrap::decrement(self.index, self.items.len());
+        &self.items[self.index]
+    }
+
+    /// Get an immutable reference to the item `next` would return,
+    /// without moving the cursor.
+    pub fn peek(&self) -> &T {
+        &self.items[self.index]
+    }
+
+    /// Move past the next `n` items without returning them.
+    /// This takes constant time regardless of `n`.
+    pub fn skip(&mut self, n: usize) {
+        self.index = wrap::forward(self.index, n, self.items.len());
+    }
+
+    /// The index of the item `next` would return.
+    pub fn position(&self) -> usize {
+        self.index
+    }
+
+    /// Move the cursor so that `next` returns the item at `index`.
+    /// Indexes past the end wrap around, so this never panics.
+    pub fn set_position(&mut self, index: usize) {
+        self.index = index % self.items.len();
+    }
+
+    /// Iterate over every item exactly once, starting with the item `next`
+    /// would return. The cursor does not move.
+    pub fn lap(&self) -> Lap<'_, T> {
+        Lap::new(&self.items, self.index)
+    }
+
+    /// The shared items.
+    pub fn items(&self) -> &Arc<[T]> {
+        &self.items
+    }
+}
+
+// Derived Clone would needlessly require `T: Clone`.
+impl<T> Clone for Cursor<T> {
+    fn clone(&self) -> Self {
+        Cursor {
+            items: Arc::clone(&self.items),
+            index: self.index,
+        }
+    }
+}
+
+/// Moves the items into shared storage, keeping the index of the cursor.
+/// Enabled flags, the traversal mode, the stride, the lap count and wrap
+/// hooks are dropped.
+impl<T> From<CircularVec<T>> for Cursor<T> {
+    fn from(cv: CircularVec<T>) -> Self {
+        Cursor {
+            items: Arc::from(cv.items),
+            index: cv.cursor.index,
+        }
+    }
+}
+
+impl<T> TryFrom<Vec<T>> for Cursor<T> {
+    type Error = CircularVecError;
+
+    fn try_from(items: Vec<T>) -> Result<Self, Self::Error> {
+        Self::new(Arc::from(items))
+    }
+}
+
+impl<T> Deref for Cursor<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Cursor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("items", &self.items)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    // Not Clone, so any accidental copy of the items fails to compile.
+    #[derive(Debug, PartialEq)]
+    struct Mirror(&'static str);
+
+    #[test]
+    fn independent_positions() {
+        let mut a = Cursor::try_from(vec![Mirror("x"), Mirror("y"), Mirror("z")]).unwrap();
+        let mut b = a.at(2);
+        let mut c = a.clone();
+        assert_eq!(Arc::strong_count(a.items()), 3);
+
+        assert_eq!(a.next(), &Mirror("x"));
+        assert_eq!(a.next(), &Mirror("y"));
+        assert_eq!(b.next(), &Mirror("z"));
+        assert_eq!(b.next(), &Mirror("x"));
+        assert_eq!(c.prev(), &Mirror("z"));
+        assert_eq!((a.position(), b.position(), c.position()), (2, 1, 2));
+
+        c.skip(4);
+        assert_eq!(c.peek(), &Mirror("x"));
+        c.set_position(5);
+        assert_eq!(c.lap().map(|m| m.0).collect::<Vec<_>>(), ["z", "x", "y"]);
+    }
+
+    #[test]
+    fn from_circular_vec() {
+        let mut cv = CircularVec::new(1, vec![2, 3]);
+        cv.next();
+        let mut cursor = Cursor::from(cv);
+        assert_eq!(cursor.next(), &2);
+
+        let empty: Arc<[u8]> = Arc::from(Vec::new());
+        assert_eq!(Cursor::new(empty).err(), Some(CircularVecError::Empty));
+        assert_eq!(
+            Cursor::starting_at(Arc::from(vec![1, 2]), 3)
+                .unwrap()
+                .peek(),
+            &2
+        );
+    }
+}
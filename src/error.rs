@@ -0,0 +1,53 @@
+use core::error::Error;
+use core::fmt;
+
+/// Errors returned by the fallible constructors and operations in this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CircularVecError {
+    /// The operation would have produced a CircularVec without any items.
+    Empty,
+    /// A RingBuffer was given a capacity of zero.
+    ZeroCapacity,
+    /// A cursor position was outside the items it should point into.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Every item of a WeightedCircularVec would have a weight of zero.
+    ZeroTotalWeight,
+    /// Every item of a CircularVec is disabled, so there is nothing to return.
+    AllDisabled,
+    /// A CircularVec was given a stride of zero, so `next` would never move.
+    ZeroStride,
+    /// Every item of a TimedCircularVec would have a duration of zero.
+    ZeroTotalDuration,
+    /// A HashRing was asked to place each node on the ring zero times.
+    ZeroVirtualNodes,
+}
+
+impl fmt::Display for CircularVecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircularVecError::Empty => f.write_str("a CircularVec must contain at least one item"),
+            CircularVecError::ZeroCapacity => {
+                f.write_str("a RingBuffer must have a capacity of at least one")
+            }
+            CircularVecError::IndexOutOfBounds { index, len } => write!(
+                f,
+                "cursor index {} is out of bounds for {} items",
+                index, len
+            ),
+            CircularVecError::ZeroTotalWeight => {
+                f.write_str("at least one item must have a non-zero weight")
+            }
+            CircularVecError::AllDisabled => f.write_str("every item is disabled"),
+            CircularVecError::ZeroStride => f.write_str("the stride must not be zero"),
+            CircularVecError::ZeroTotalDuration => {
+                f.write_str("at least one item must have a non-zero duration")
+            }
+            CircularVecError::ZeroVirtualNodes => {
+                f.write_str("a HashRing must have at least one virtual node per node")
+            }
+        }
+    }
+}
+
+impl Error for CircularVecError {}
@@ -0,0 +1,394 @@
+//! A consistent hashing ring that maps keys to nodes.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+use crate::CircularVecError;
+
+/// A consistent hashing ring, for spreading keys such as cache entries
+/// across a changing set of nodes.
+///
+/// Each node is hashed onto a circle of `u64` positions several times,
+/// once per virtual node, and a key belongs to the first node found by
+/// walking clockwise from the key's own position. Adding or removing a
+/// node only moves the keys next to that node's positions, about `1/n`
+/// of them for `n` nodes, instead of reshuffling almost every key the way
+/// `hash % n` does. More virtual nodes spread the keys more evenly.
+///
+///     # use circular_vec::HashRing;
+///     let mut ring = HashRing::new(100);
+///     ring.add("cache-1");
+///     ring.add("cache-2");
+///     let owner = *ring.get("user:42").unwrap();
+///
+///     // Adding a node only ever moves keys onto the new node.
+///     let before = ring.clone();
+///     ring.add("cache-3");
+///     let now = *ring.get("user:42").unwrap();
+///     assert!(now == owner || now == "cache-3");
+///     assert_eq!(ring.get_n("user:42", 2).len(), 2);
+///     # let keys: Vec<String> = (0..1000).map(|i| format!("user:{}", i)).collect();
+///     # assert!(ring.moved_keys(&before, keys.iter().map(String::as_str)) < 500);
+///
+/// Positions come from `S`, which defaults to `RingHasher` so that
+/// processes built for the same target with the same toolchain agree on
+/// which node owns a key. See `RingHasher` for the limits of that.
+#[derive(Clone)]
+pub struct HashRing<N, S = BuildHasherDefault<RingHasher>> {
+    // The distinct nodes, in the order they were added.
+    nodes: Vec<N>,
+    // Every virtual node, sorted clockwise by position.
+    points: Vec<Point>,
+    virtual_nodes: usize,
+    hash_builder: S,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Point {
+    hash: u64,
+    // Index into `nodes`.
+    node: usize,
+}
+
+/// How much of a HashRing changed owner when a node was added or
+/// removed. Returned by `HashRing::add` and `HashRing::remove`.
+///
+/// This is measured from the node's share of the ring, so it is what
+/// to expect for evenly hashed keys. `HashRing::moved_keys` counts the
+/// keys that actually moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rebalance {
+    // The length of the arcs that changed owner, out of 2^64.
+    moved: u128,
+}
+
+const RING_SIZE: u128 = 1 << 64;
+
+impl Rebalance {
+    /// The share of all keys that moved, between 0 and 1.
+    pub fn fraction(&self) -> f64 {
+        self.moved as f64 / RING_SIZE as f64
+    }
+
+    /// How many of `keys` evenly hashed keys can be expected to have
+    /// moved, rounded down.
+    pub fn expected_moved(&self, keys: usize) -> usize {
+        (self.moved * keys as u128 / RING_SIZE) as usize
+    }
+}
+
+impl<N> HashRing<N> {
+    /// Create an empty HashRing that places each node on the ring
+    /// `virtual_nodes` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `virtual_nodes` is zero. Use `try_new` to handle that
+    /// case without panicking.
+    pub fn new(virtual_nodes: usize) -> Self {
+        Self::with_hasher(virtual_nodes, BuildHasherDefault::default())
+    }
+
+    /// Create an empty HashRing that places each node on the ring
+    /// `virtual_nodes` times, returning
+    /// `CircularVecError::ZeroVirtualNodes` if `virtual_nodes` is zero.
+    pub fn try_new(virtual_nodes: usize) -> Result<Self, CircularVecError> {
+        Self::try_with_hasher(virtual_nodes, BuildHasherDefault::default())
+    }
+}
+
+impl<N, S> HashRing<N, S> {
+    /// Create an empty HashRing that positions nodes and keys with
+    /// `hash_builder`. Every process sharing the ring must use a builder
+    /// that hashes the same way, so a randomly seeded one such as std's
+    /// `RandomState` is only suitable within a single process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `virtual_nodes` is zero.
+    pub fn with_hasher(virtual_nodes: usize, hash_builder: S) -> Self {
+        match Self::try_with_hasher(virtual_nodes, hash_builder) {
+            Ok(ring) => ring,
+            Err(e) => panic!("cannot create HashRing: {}", e),
+        }
+    }
+
+    /// Like `with_hasher`, but returns `CircularVecError::ZeroVirtualNodes`
+    /// instead of panicking if `virtual_nodes` is zero.
+    pub fn try_with_hasher(
+        virtual_nodes: usize,
+        hash_builder: S,
+    ) -> Result<Self, CircularVecError> {
+        if virtual_nodes == 0 {
+            return Err(CircularVecError::ZeroVirtualNodes);
+        }
+        Ok(HashRing {
+            nodes: Vec::new(),
+            points: Vec::new(),
+            virtual_nodes,
+            hash_builder,
+        })
+    }
+
+    /// The nodes on the ring, in the order they were added.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// The number of nodes on the ring.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Whether the ring has no nodes, in which case no key has an owner.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// How many times each node is placed on the ring.
+    pub fn virtual_nodes(&self) -> usize {
+        self.virtual_nodes
+    }
+
+    /// The index into `points` of the first point at or clockwise from
+    /// `hash`, or `None` if the ring is empty.
+    fn successor(&self, hash: u64) -> Option<usize> {
+        if self.points.is_empty() {
+            return None;
+        }
+        match self.points.partition_point(|point| point.hash < hash) {
+            i if i == self.points.len() => Some(0),
+            i => Some(i),
+        }
+    }
+
+    /// The length of the arcs owned by the node at `node`: those ending
+    /// at one of its points.
+    fn owned_by(&self, node: usize) -> u128 {
+        if self.nodes.len() == 1 {
+            return RING_SIZE;
+        }
+        let last = self.points.len() - 1;
+        let previous = core::iter::once(self.points[last].hash)
+            .chain(self.points[..last].iter().map(|point| point.hash));
+        self.points
+            .iter()
+            .zip(previous)
+            .filter(|(point, _)| point.node == node)
+            .map(|(point, previous)| u128::from(point.hash.wrapping_sub(previous)))
+            .sum()
+    }
+}
+
+impl<N: Hash + Eq, S: BuildHasher> HashRing<N, S> {
+    /// Add a node to the ring, returning how much of the ring moved to
+    /// it, or `None` without changing anything if it is already on the
+    /// ring. Only keys that now belong to `node` move.
+    pub fn add(&mut self, node: N) -> Option<Rebalance> {
+        if self.contains(&node) {
+            return None;
+        }
+        let index = self.nodes.len();
+        for replica in 0..self.virtual_nodes as u64 {
+            let mut hasher = self.hash_builder.build_hasher();
+            node.hash(&mut hasher);
+            // Fixed byte order, so the positions do not depend on the target.
+            hasher.write(&replica.to_le_bytes());
+            let hash = hasher.finish();
+            self.points.push(Point { hash, node: index });
+        }
+        self.points
+            .sort_unstable_by_key(|point| (point.hash, point.node));
+        self.nodes.push(node);
+        Some(Rebalance {
+            moved: self.owned_by(index),
+        })
+    }
+
+    /// Remove a node from the ring, returning how much of the ring moved
+    /// away from it, or `None` if it was not on the ring. Only keys that
+    /// belonged to `node` move.
+    pub fn remove(&mut self, node: &N) -> Option<Rebalance> {
+        let index = self.nodes.iter().position(|n| n == node)?;
+        let moved = self.owned_by(index);
+        self.nodes.remove(index);
+        self.points.retain(|point| point.node != index);
+        for point in &mut self.points {
+            if point.node > index {
+                point.node -= 1;
+            }
+        }
+        Some(Rebalance { moved })
+    }
+
+    /// Whether `node` is on the ring.
+    pub fn contains(&self, node: &N) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// The node that owns `key`, or `None` if the ring is empty.
+    pub fn get<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        let start = self.successor(self.hash_builder.hash_one(key))?;
+        Some(&self.nodes[self.points[start].node])
+    }
+
+    /// Up to `replicas` distinct nodes for `key`, found by walking
+    /// clockwise from it, for placing copies of a key on several nodes.
+    /// The first is the owner returned by `get`. Fewer are returned if
+    /// the ring has fewer nodes.
+    pub fn get_n<K: Hash + ?Sized>(&self, key: &K, replicas: usize) -> Vec<&N> {
+        let mut found = Vec::new();
+        let start = match self.successor(self.hash_builder.hash_one(key)) {
+            Some(start) => start,
+            None => return found,
+        };
+        let wanted = replicas.min(self.nodes.len());
+        let mut seen = vec![false; self.nodes.len()];
+        let clockwise = self.points[start..].iter().chain(&self.points[..start]);
+        for point in clockwise {
+            if found.len() == wanted {
+                break;
+            }
+            if !seen[point.node] {
+                seen[point.node] = true;
+                found.push(&self.nodes[point.node]);
+            }
+        }
+        found
+    }
+
+    /// How many of `keys` have a different owner on this ring than on
+    /// `before`, such as a clone taken before adding or removing nodes.
+    pub fn moved_keys<'a, K, I>(&self, before: &HashRing<N, S>, keys: I) -> usize
+    where
+        K: Hash + ?Sized + 'a,
+        I: IntoIterator<Item = &'a K>,
+    {
+        keys.into_iter()
+            .filter(|key| self.get(*key) != before.get(*key))
+            .count()
+    }
+}
+
+impl<N: fmt::Debug, S> fmt::Debug for HashRing<N, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashRing")
+            .field("nodes", &self.nodes)
+            .field("virtual_nodes", &self.virtual_nodes)
+            .finish()
+    }
+}
+
+/// The default hasher for a HashRing: 64-bit FNV-1a, finished with the
+/// MurmurHash3 mixer so that similar keys land far apart on the ring.
+///
+/// Unlike std's `RandomState` it is not seeded, so every process built
+/// for the same target with the same toolchain puts a key in the same
+/// place. Beyond that there is no guarantee, because it only sees the
+/// bytes that a key's `Hash` impl feeds it: integers are fed in native
+/// byte order, `usize` depends on the pointer width, and std does not
+/// promise that its `Hash` impls stay the same between Rust versions.
+/// To share a ring across targets, prefer string keys and nodes, whose
+/// bytes do not depend on the target, over integers, and over slices,
+/// which are prefixed with their length as a `usize`.
+#[derive(Clone, Copy, Debug)]
+pub struct RingHasher(u64);
+
+impl Default for RingHasher {
+    fn default() -> Self {
+        RingHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for RingHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut hash = self.0;
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        hash ^ (hash >> 33)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(nodes: &[&'static str]) -> HashRing<&'static str> {
+        let mut ring = HashRing::new(100);
+        for &node in nodes {
+            ring.add(node);
+        }
+        ring
+    }
+
+    #[test]
+    fn keys_are_spread_over_nodes() {
+        let ring = ring(&["a", "b", "c"]);
+        let mut counts = [0; 3];
+        for key in 0..3000u32 {
+            let owner = ring.get(&key).unwrap();
+            counts[ring.nodes().iter().position(|n| n == owner).unwrap()] += 1;
+        }
+        assert!(counts.iter().all(|&count| count > 700), "{:?}", counts);
+        assert_eq!(HashRing::<&str>::new(1).get("key"), None);
+    }
+
+    #[test]
+    fn replicas_are_distinct() {
+        let ring = ring(&["a", "b", "c"]);
+        for key in 0..100u32 {
+            let two = ring.get_n(&key, 2);
+            assert_eq!(two.len(), 2);
+            assert_ne!(two[0], two[1]);
+            assert_eq!(Some(two[0]), ring.get(&key));
+            assert_eq!(ring.get_n(&key, 10).len(), 3);
+        }
+        assert!(ring.get_n(&0u32, 0).is_empty());
+    }
+
+    #[test]
+    fn membership_changes_move_few_keys() {
+        let keys: Vec<u32> = (0..10_000).collect();
+        let mut ring = ring(&["a", "b", "c"]);
+        let before = ring.clone();
+
+        let added = ring.add("d").unwrap();
+        assert_eq!(ring.add("d"), None);
+        for key in &keys {
+            let owner = ring.get(key).unwrap();
+            assert!(owner == before.get(key).unwrap() || *owner == "d");
+        }
+        let moved = ring.moved_keys(&before, &keys);
+        assert!(moved > 1_500 && moved < 3_500, "{}", moved);
+        let expected = added.expected_moved(keys.len());
+        assert!(moved.abs_diff(expected) < 500, "{} vs {}", moved, expected);
+        assert!((0.15..0.35).contains(&added.fraction()));
+
+        // Removing the node moves exactly its keys back.
+        assert_eq!(ring.remove(&"d"), Some(added));
+        assert_eq!(ring.moved_keys(&before, &keys), 0);
+        assert_eq!(ring.remove(&"d"), None);
+        ring.remove(&"a");
+        ring.remove(&"b");
+        assert_eq!(ring.remove(&"c").unwrap().fraction(), 1.0);
+    }
+
+    #[test]
+    fn reject_zero_virtual_nodes() {
+        assert_eq!(
+            HashRing::<u8>::try_new(0).err(),
+            Some(CircularVecError::ZeroVirtualNodes)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Finite iterators over the items of a CircularVec.
+
+use core::fmt;
+use core::iter::{Chain, FusedIterator};
+use core::slice;
+
+use crate::wrap;
+
+/// Iterator over every item of a CircularVec exactly once, starting at
+/// the cursor. Created by `CircularVec::lap` or by iterating over
+/// `&CircularVec`.
+#[derive(Debug)]
+pub struct Lap<'a, T> {
+    inner: Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Lap<'a, T> {
+    pub(crate) fn new(items: &'a [T], index: usize) -> Self {
+        let (before, after) = items.split_at(index);
+        Lap {
+            inner: after.iter().chain(before.iter()),
+            remaining: items.len(),
+        }
+    }
+}
+
+// Derived Clone would needlessly require `T: Clone`.
+impl<'a, T> Clone for Lap<'a, T> {
+    fn clone(&self) -> Self {
+        Lap {
+            inner: self.inner.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Lap<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Lap<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Lap<'a, T> {}
+
+impl<'a, T> FusedIterator for Lap<'a, T> {}
+
+/// Iterator over as many items as a CircularVec holds, starting at the
+/// cursor and moving the cursor past each item as it is yielded.
+/// Created by `CircularVec::advance_lap`.
+///
+/// Each item is what `next` would have returned, ignoring enabled flags.
+/// In the default `TraversalMode::Wrap` that is every item exactly once,
+/// and consuming the whole iterator leaves the cursor where it started.
+/// Dropping it early leaves the cursor after the last yielded item, just
+/// as if `next` had been called that many times, including counting a
+/// lap if one was completed.
+pub struct AdvancingLap<'a, T> {
+    items: &'a [T],
+    cursor: &'a mut dyn Advance,
+    remaining: usize,
+}
+
+/// A cursor that an `AdvancingLap` can move on by one item.
+pub(crate) trait Advance {
+    /// Move past the next of `len` items, returning its index.
+    fn advance(&mut self, len: usize) -> usize;
+}
+
+/// A plain index that wraps from the last item to the first.
+impl Advance for usize {
+    fn advance(&mut self, len: usize) -> usize {
+        let index = *self;
+        *self = wrap::increment(index, len);
+        index
+    }
+}
+
+impl<'a, T> AdvancingLap<'a, T> {
+    pub(crate) fn new(items: &'a [T], cursor: &'a mut dyn Advance) -> Self {
+        AdvancingLap {
+            items,
+            cursor,
+            remaining: items.len(),
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for AdvancingLap<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdvancingLap")
+            .field("items", &self.items)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+impl<'a, T> Iterator for AdvancingLap<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(&self.items[self.cursor.advance(self.items.len())])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for AdvancingLap<'a, T> {}
+
+impl<'a, T> FusedIterator for AdvancingLap<'a, T> {}
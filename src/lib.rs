@@ -1,8 +1,8 @@
-//! This struct maintains a fixed length circular Vec.
-//! You provide the items for the circular Vec at initialization
-//! time and can then never change the amount of items held within.
-//! It provides a `next` function that, when it hits the end of the struct,
-//! just loops back to the start.
+//! This crate maintains circular collections.
+//! You provide the items for a CircularVec at initialization
+//! time and it then loops over them forever. It provides a `next`
+//! function that, when it hits the end of the items, just loops back
+//! to the start.
 //!
 //! The CircularVec allows for in place changes to the items held
 //! within using the `next_mut` function, though for most cases
@@ -10,15 +10,88 @@
 //! you must have a mutable reference to the CircularVec so that
 //! it can increment its internal counter.
 //!
-//! Notably, CircularVec does not implement `IntoIterator` because it
-//! would produce an iterator that never ends, which is not the
-//! intended use of `IntoIterator`. Accordingly, the `next` function
-//! here does not return the item (`T`), but a reference to it (`&T`), and
-//! returns `&T` instead of `Option<&T>` because there will always
-//! be an item it can return.
+//! Notably, CircularVec does not implement `IntoIterator` for the
+//! looping behaviour of `next` because it would produce an iterator
+//! that never ends, which is not the intended use of `IntoIterator`.
+//! Accordingly, the `next` function here does not return the item
+//! (`T`), but a reference to it (`&T`), and returns `&T` instead of
+//! `Option<&T>` because a CircularVec is never empty. The one exception
+//! is when every item has been disabled with `disable`: `next`,
+//! `next_mut` and `next_with_wrap` then panic, and their `try_next`,
+//! `try_next_mut` and `try_next_with_wrap` forms return
+//! `CircularVecError::AllDisabled` instead.
+//!
+//! A CircularVec always holds at least one item. Empty input is
+//! rejected at construction time: use `CircularVec::new`, which takes
+//! the first item separately, or the fallible `try_from_iter` and
+//! `TryFrom<Vec<T>>` conversions, which return `CircularVecError::Empty`.
+//! Collecting an empty iterator into a CircularVec panics.
+//!
+//! For bounded iteration, `lap` visits every item once starting at the
+//! cursor. Iterating over `&CircularVec` does the same, so it works with
+//! `for` loops and stops after one lap. `laps(n)` repeats that `n`
+//! times, and `cycle` is an explicit opt-in to the infinite version.
+//!
+//! `range_wrapping` gives a view of a run of items that may cross from
+//! the last item back to the first, such as a sliding window.
+//!
+//! `next` wraps from the last item back to the first by default. For
+//! animations and sweeps, `TraversalMode::Bounce` makes it go back and
+//! forth instead, and `position` then also reports the direction.
+//!
+//! For keeping the last N items of an unbounded stream, such as a
+//! history of recent events, see `RingBuffer`, which has a fixed
+//! capacity and overwrites its oldest item when pushed to while full.
+//!
+//! When many consumers each need their own position over one list, give
+//! each a `Cursor`. Cursors share the items through an `Arc<[T]>`, so
+//! making another one never copies them.
+//!
+//! `next` needs `&mut self`. To pick items round-robin from many threads
+//! at once without a lock, use `SharedCircularVec`, whose cursor is an
+//! atomic counter.
+//!
+//! To return some items more often than others without repeating them,
+//! use `WeightedCircularVec`, which implements smooth weighted
+//! round-robin.
+//!
+//! `TimedCircularVec` gives each item its own duration and works out
+//! which item is current at a given time, for animations and schedules.
+//! It reads the time from a `Clock`, which tests can replace with a
+//! `ManualClock`.
+//!
+//! `HashRing` is a consistent hashing ring with virtual nodes, for
+//! sharding keys across nodes that come and go while moving as few keys
+//! as possible.
+//!
+//! For rotating through credentials such as API keys, which get rate
+//! limited, `CooldownCircularVec` passes over items that are in cooldown
+//! or out of tokens, and says how long to wait when none are left.
+//!
+//! The crate is `no_std`. CircularVec and RingBuffer need an allocator
+//! and sit behind the `alloc` feature, which is enabled by the default
+//! `std` feature. `CircularArray` offers the same looping API over a
+//! fixed size array and is always available.
+//!
+//! With the `serde` feature enabled, CircularVec implements `Serialize`
+//! and `Deserialize`, keeping both the items and the cursor position.
+//! See `items_only` for a representation without the cursor.
+//!
+//! With the `rand` feature enabled, `ShuffledCircularVec` visits every
+//! item once per lap in a random order, reshuffling as each lap ends.
+//!
+//! With the `rayon` feature enabled, `par_lap` and `IntoParallelIterator`
+//! on `&CircularVec` and `&mut CircularVec` spread one lap across rayon's
+//! thread pool, keeping lap order for indexed operations.
+//!
+//! With the `tokio` feature enabled, `into_stream` turns a CircularVec
+//! into an `ItemStream` that yields the next item on every tick of a
+//! Tokio interval, and can be paused, resumed and retimed while running.
 //!
 //! Example usage:
 //!
+//!     # #[cfg(feature = "alloc")] {
+//!     # use circular_vec::CircularVec;
 //!     let mut cv: CircularVec<String> = ["hello".to_string(), "world".to_string()]
 //!         .to_vec()
 //!         .into_iter()
@@ -28,99 +101,79 @@
 //!     assert_eq!(cv.next(), "world");
 //!     assert_eq!(cv.next(), "hello");
 //!     assert_eq!(cv.next(), "world");
-
-use std::iter::FromIterator;
-use std::ops::{Index, IndexMut};
-use std::slice::SliceIndex;
-
-/// See crate level documentation.
-pub struct CircularVec<T> {
-    items: Vec<T>,
-    index: usize,
-}
-
-impl<T> CircularVec<T> {
-    fn new(items: Vec<T>) -> Self {
-        CircularVec { items, index: 0 }
-    }
-
-    /// Get an immutable reference to the next item in the CircularVec.
-    pub fn next(&mut self) -> &T {
-        let original_index = self.index;
-        self.increment_index();
-        &self.items[original_index]
-    }
-
-    pub fn skip(&mut self, n: usize) {
-        let mut n = n;
-        while n > 0 {
-            self.increment_index();
-            n -= 1;
-        }
-    }
-    /// Get a mutable reference to the next item in the CircularVec.
-    pub fn next_mut(&mut self) -> &mut T {
-        let original_index = self.index;
-        self.increment_index();
-        IndexMut::index_mut(&mut *self.items, original_index)
-    }
-
-    fn increment_index(&mut self) {
-        self.index = (self.index + 1) % self.items.len();
-    }
-}
-
-impl<T> FromIterator<T> for CircularVec<T> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut items = Vec::new();
-
-        for i in iter {
-            items.push(i);
-        }
-
-        Self::new(items)
-    }
-}
-
-impl<T, I: SliceIndex<[T]>> Index<I> for CircularVec<T> {
-    type Output = I::Output;
-
-    #[inline]
-    fn index(&self, index: I) -> &Self::Output {
-        Index::index(&*self.items, index)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn loop_through() {
-        let mut cv: CircularVec<u64> = [50, 60, 70, 80].to_vec().into_iter().collect();
-        assert_eq!(cv.next(), &50);
-        assert_eq!(cv.next(), &60);
-        assert_eq!(cv.next(), &70);
-        assert_eq!(cv.next(), &80);
-        assert_eq!(cv.next(), &50);
-
-        assert_eq!(cv[0], 50);
-    }
-
-    #[test]
-    fn loop_through_string() {
-        let mut cv: CircularVec<String> = ["hello".to_string(), "world".to_string()]
-            .to_vec()
-            .into_iter()
-            .collect();
-
-        assert_eq!(cv.next(), "hello");
-        assert_eq!(cv.next(), "world");
-        assert_eq!(cv.next(), "hello");
-        assert_eq!(cv.next(), "world");
-        assert_eq!(cv.next(), "hello");
-        assert_eq!(cv.next(), "world");
-
-        assert_eq!(cv[0], "hello");
-    }
-}
+//!     # }
+
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+mod array;
+#[cfg(feature = "alloc")]
+mod circular_vec;
+#[cfg(feature = "std")]
+mod clock;
+#[cfg(feature = "std")]
+mod cooldown;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod cursor;
+mod error;
+#[cfg(feature = "alloc")]
+pub mod hash_ring;
+pub mod iter;
+#[cfg(feature = "rayon")]
+mod rayon_impl;
+#[cfg(feature = "alloc")]
+pub mod ring_buffer;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+mod shared;
+#[cfg(feature = "rand")]
+mod shuffled;
+#[cfg(feature = "alloc")]
+pub mod slice;
+#[cfg(feature = "tokio")]
+mod stream;
+#[cfg(feature = "std")]
+mod timed;
+#[cfg(feature = "alloc")]
+mod traversal;
+#[cfg(feature = "alloc")]
+mod weighted;
+mod wrap;
+
+pub use array::CircularArray;
+#[cfg(feature = "alloc")]
+pub use circular_vec::{CircularVec, Wrapping};
+#[cfg(feature = "std")]
+pub use clock::{Clock, ManualClock, SystemClock};
+#[cfg(feature = "std")]
+pub use cooldown::CooldownCircularVec;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use cursor::Cursor;
+pub use error::CircularVecError;
+#[cfg(feature = "alloc")]
+pub use hash_ring::HashRing;
+#[cfg(feature = "rayon")]
+pub use rayon_impl::{ParLap, ParLapMut};
+#[cfg(feature = "alloc")]
+pub use ring_buffer::RingBuffer;
+#[cfg(feature = "serde")]
+pub use serde_impl::items_only;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub use shared::SharedCircularVec;
+#[cfg(feature = "rand")]
+pub use shuffled::ShuffledCircularVec;
+#[cfg(feature = "alloc")]
+pub use slice::{CircularSlice, CircularSliceMut};
+#[cfg(feature = "tokio")]
+pub use stream::{ItemStream, StreamHandle};
+#[cfg(feature = "std")]
+pub use timed::TimedCircularVec;
+#[cfg(feature = "alloc")]
+pub use traversal::{Direction, Position, TraversalMode};
+#[cfg(feature = "alloc")]
+pub use weighted::WeightedCircularVec;
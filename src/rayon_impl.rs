@@ -0,0 +1,91 @@
+use rayon::iter::{Chain, IntoParallelIterator, ParallelIterator};
+use rayon::slice;
+
+use crate::CircularVec;
+
+/// Parallel iterator over every item of a CircularVec exactly once,
+/// starting at the cursor. Created by `CircularVec::par_lap` or by
+/// calling `into_par_iter` on `&CircularVec`.
+///
+/// It is indexed, so order-preserving operations such as `collect` into
+/// a Vec or `enumerate` see the items in lap order, just like `lap`.
+pub type ParLap<'a, T> = Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>;
+
+/// Mutable parallel iterator over every item of a CircularVec exactly
+/// once, starting at the cursor. Created by calling `into_par_iter` on
+/// `&mut CircularVec`.
+pub type ParLapMut<'a, T> = Chain<slice::IterMut<'a, T>, slice::IterMut<'a, T>>;
+
+impl<T: Sync> CircularVec<T> {
+    /// Like `lap`, but split across rayon's thread pool. The cursor does
+    /// not move.
+    ///
+    ///     # use circular_vec::CircularVec;
+    ///     use rayon::prelude::*;
+    ///
+    ///     let mut cv = CircularVec::from([1, 2, 3, 4]);
+    ///     cv.next();
+    ///     let doubled: Vec<i32> = cv.par_lap().map(|x| x * 2).collect();
+    ///     assert_eq!(doubled, [4, 6, 8, 2]);
+    pub fn par_lap(&self) -> ParLap<'_, T> {
+        let (before, after) = self.items.split_at(self.cursor.index);
+        after.into_par_iter().chain(before)
+    }
+}
+
+/// A single lap starting at the cursor, as returned by
+/// `CircularVec::par_lap`, matching the sequential `IntoIterator`.
+impl<'a, T: Sync> IntoParallelIterator for &'a CircularVec<T> {
+    type Item = &'a T;
+    type Iter = ParLap<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.par_lap()
+    }
+}
+
+/// A single lap starting at the cursor, yielding mutable references.
+impl<'a, T: Send> IntoParallelIterator for &'a mut CircularVec<T> {
+    type Item = &'a mut T;
+    type Iter = ParLapMut<'a, T>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        let (before, after) = self.items.split_at_mut(self.cursor.index);
+        after.into_par_iter().chain(before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use rayon::iter::IndexedParallelIterator;
+
+    #[test]
+    fn par_lap_keeps_cursor_order() {
+        let mut cv = CircularVec::try_from_iter(0..1000).unwrap();
+        cv.skip(990);
+        let lap: Vec<i32> = cv.par_lap().copied().collect();
+        assert_eq!(lap, cv.lap().copied().collect::<Vec<_>>());
+        assert_eq!(cv.par_lap().len(), 1000);
+
+        let firsts: Vec<(usize, i32)> = (&cv)
+            .into_par_iter()
+            .enumerate()
+            .map(|(i, &x)| (i, x))
+            .filter(|&(i, _)| i < 2)
+            .collect();
+        assert_eq!(firsts, [(0, 990), (1, 991)]);
+        assert_eq!(cv.position().index, 990);
+    }
+
+    #[test]
+    fn mutable_parallel_lap() {
+        let mut cv = CircularVec::try_from_iter(0..100u64).unwrap();
+        cv.skip(50);
+        (&mut cv).into_par_iter().for_each(|x| *x *= 2);
+        assert_eq!(cv.iter().sum::<u64>(), 9900);
+        let order: Vec<u64> = (&mut cv).into_par_iter().map(|x| *x).collect();
+        assert_eq!(order[..2], [100, 102]);
+    }
+}
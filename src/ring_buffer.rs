@@ -0,0 +1,264 @@
+//! A fixed capacity buffer that overwrites its oldest item when full.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::{Chain, FusedIterator};
+use core::mem;
+use core::slice;
+
+use crate::wrap;
+use crate::CircularVecError;
+
+/// A fixed capacity buffer that keeps the most recently pushed items.
+///
+/// Unlike CircularVec, a RingBuffer starts out empty and fills up as
+/// items are pushed. Once it is full, each `push` overwrites the oldest
+/// item and hands it back to the caller.
+///
+///     # use circular_vec::RingBuffer;
+///     let mut history = RingBuffer::new(2);
+///     assert_eq!(history.push("a"), None);
+///     assert_eq!(history.push("b"), None);
+///     assert_eq!(history.push("c"), Some("a"));
+///     assert_eq!(history.iter().collect::<Vec<_>>(), [&"b", &"c"]);
+#[derive(Clone)]
+pub struct RingBuffer<T> {
+    items: Vec<T>,
+    capacity: usize,
+    // Index of the oldest item. Always 0 until the buffer is full.
+    start: usize,
+}
+
+impl<T> RingBuffer<T> {
+    /// Create an empty RingBuffer that holds up to `capacity` items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero. Use `try_new` to handle that case
+    /// without panicking.
+    pub fn new(capacity: usize) -> Self {
+        match Self::try_new(capacity) {
+            Ok(rb) => rb,
+            Err(e) => panic!("cannot create RingBuffer: {}", e),
+        }
+    }
+
+    /// Create an empty RingBuffer that holds up to `capacity` items,
+    /// returning `CircularVecError::ZeroCapacity` if `capacity` is zero.
+    pub fn try_new(capacity: usize) -> Result<Self, CircularVecError> {
+        if capacity == 0 {
+            return Err(CircularVecError::ZeroCapacity);
+        }
+        Ok(RingBuffer {
+            // Grown by `push`, so a large capacity costs nothing until
+            // it is used.
+            items: Vec::new(),
+            capacity,
+            start: 0,
+        })
+    }
+
+    /// Add an item as the newest one. If the buffer is already full, the
+    /// oldest item is overwritten and returned.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        if !self.is_full() {
+            if self.items.len() == self.items.capacity() {
+                // Grow like a Vec would, but never past `capacity`.
+                let room = self.capacity - self.items.len();
+                self.items.reserve_exact(self.items.len().max(4).min(room));
+            }
+            self.items.push(item);
+            return None;
+        }
+        let evicted = mem::replace(&mut self.items[self.start], item);
+        self.start = wrap::increment(self.start, self.capacity);
+        Some(evicted)
+    }
+
+    /// The most recently pushed item, if any.
+    pub fn latest(&self) -> Option<&T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        Some(&self.items[wrap::decrement(self.start, self.items.len())])
+    }
+
+    /// The oldest item still held, if any.
+    pub fn oldest(&self) -> Option<&T> {
+        self.items.get(self.start)
+    }
+
+    /// Get the item at `index`, counting from the oldest item.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.items.len() {
+            return None;
+        }
+        Some(&self.items[wrap::forward(self.start, index, self.items.len())])
+    }
+
+    /// Iterate over the items from oldest to newest.
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (older, newer) = self.as_slices();
+        Iter {
+            inner: older.iter().chain(newer.iter()),
+            remaining: self.items.len(),
+        }
+    }
+
+    /// The items as two slices which, concatenated, run from oldest to
+    /// newest. The second slice is empty unless the buffer has wrapped.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (newer, older) = self.items.split_at(self.start);
+        (older, newer)
+    }
+
+    /// The number of items currently held.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether nothing has been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Whether the next `push` will evict the oldest item.
+    pub fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+
+    /// The maximum number of items held at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Lists the items from oldest to newest, like `iter`.
+        struct Items<'a, T>(&'a RingBuffer<T>);
+
+        impl<'a, T: fmt::Debug> fmt::Debug for Items<'a, T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_list().entries(self.0).finish()
+            }
+        }
+
+        f.debug_struct("RingBuffer")
+            .field("items", &Items(self))
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the items of a RingBuffer from oldest to newest,
+/// created by `RingBuffer::iter`.
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    inner: Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn fill_then_overwrite() {
+        let mut rb = RingBuffer::new(3);
+        assert!(rb.is_empty());
+        assert_eq!(rb.latest(), None);
+        assert_eq!(rb.oldest(), None);
+
+        assert_eq!(rb.push(1), None);
+        assert_eq!(rb.push(2), None);
+        assert!(!rb.is_full());
+        assert_eq!(rb.push(3), None);
+        assert!(rb.is_full());
+        assert_eq!(rb.as_slices(), (&[1, 2, 3][..], &[][..]));
+
+        assert_eq!(rb.push(4), Some(1));
+        assert_eq!(rb.push(5), Some(2));
+        assert_eq!(rb.len(), 3);
+        assert_eq!(rb.oldest(), Some(&3));
+        assert_eq!(rb.latest(), Some(&5));
+        assert_eq!(rb.get(1), Some(&4));
+        assert_eq!(rb.get(3), None);
+        assert_eq!(rb.as_slices(), (&[3][..], &[4, 5][..]));
+        assert_eq!(rb.iter().copied().collect::<Vec<_>>(), [3, 4, 5]);
+        assert_eq!(rb.iter().rev().copied().collect::<Vec<_>>(), [5, 4, 3]);
+        assert_eq!(rb.iter().len(), 3);
+        assert_eq!(
+            format!("{:?}", rb),
+            "RingBuffer { items: [3, 4, 5], capacity: 3 }"
+        );
+    }
+
+    #[test]
+    fn capacity_is_not_allocated_up_front() {
+        let mut rb = RingBuffer::new(usize::MAX / 2);
+        for i in 0..100u64 {
+            assert_eq!(rb.push(i), None);
+        }
+        assert_eq!(rb.len(), 100);
+        assert_eq!(rb.latest(), Some(&99));
+
+        let mut small = RingBuffer::new(6);
+        for i in 0..10 {
+            small.push(i);
+        }
+        assert_eq!(small.items.capacity(), 6);
+    }
+
+    #[test]
+    fn capacity_one() {
+        let mut rb = RingBuffer::new(1);
+        assert_eq!(rb.push('a'), None);
+        assert_eq!(rb.push('b'), Some('a'));
+        assert_eq!(rb.oldest(), rb.latest());
+    }
+
+    #[test]
+    fn zero_capacity() {
+        assert_eq!(
+            RingBuffer::<u8>::try_new(0).err(),
+            Some(CircularVecError::ZeroCapacity)
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use alloc::vec::Vec;
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{CircularVec, CircularVecError, Direction, TraversalMode};
+
+// The traversal mode, direction and stride are left out when they have
+// their default values, so CircularVecs that step through their items
+// one at a time keep the shape they had before those settings existed.
+#[derive(serde::Serialize)]
+#[serde(rename = "CircularVec")]
+struct Repr<'a, T> {
+    items: &'a [T],
+    index: usize,
+    #[serde(skip_serializing_if = "is_default")]
+    mode: TraversalMode,
+    #[serde(skip_serializing_if = "is_default")]
+    direction: Direction,
+    #[serde(skip_serializing_if = "is_unit_stride")]
+    stride: isize,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "CircularVec")]
+struct OwnedRepr<T> {
+    items: Vec<T>,
+    index: usize,
+    #[serde(default)]
+    mode: TraversalMode,
+    #[serde(default)]
+    direction: Direction,
+    #[serde(default = "unit_stride")]
+    stride: isize,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+fn is_unit_stride(stride: &isize) -> bool {
+    *stride == 1
+}
+
+fn unit_stride() -> isize {
+    1
+}
+
+/// Serialized as a struct holding the items, the cursor position and,
+/// where they are not the defaults, the traversal mode, direction and
+/// stride, so `next` carries on where it left off after a round trip.
+impl<T: Serialize> Serialize for CircularVec<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let position = self.position();
+        Repr {
+            items: &self.items,
+            index: position.index,
+            mode: self.traversal_mode(),
+            direction: position.direction,
+            stride: self.stride(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Fails if there are no items, the cursor position is out of range or
+/// the stride is zero.
+/// A direction the traversal mode cannot have at that index, such as
+/// `Backward` in `TraversalMode::Wrap`, is corrected rather than rejected.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for CircularVec<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let OwnedRepr {
+            items,
+            index,
+            mode,
+            direction,
+            stride,
+        } = OwnedRepr::deserialize(deserializer)?;
+        let len = items.len();
+        let mut cv = CircularVec::try_from_iter(items).map_err(D::Error::custom)?;
+        if index >= len {
+            return Err(D::Error::custom(CircularVecError::IndexOutOfBounds {
+                index,
+                len,
+            }));
+        }
+        cv.cursor.index = index;
+        cv.cursor.direction = direction;
+        cv.set_traversal_mode(mode);
+        cv.set_stride(stride).map_err(D::Error::custom)?;
+        Ok(cv)
+    }
+}
+
+/// Serialize a CircularVec as a plain sequence of its items, dropping the
+/// cursor. Deserializing puts the cursor on the first item and fails if
+/// the sequence is empty. Use it with
+/// `#[serde(with = "circular_vec::items_only")]`.
+pub mod items_only {
+    use super::*;
+
+    pub fn serialize<T, S>(cv: &CircularVec<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        cv.items.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<CircularVec<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let items = Vec::deserialize(deserializer)?;
+        CircularVec::try_from_iter(items).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+
+    #[test]
+    fn round_trip_keeps_cursor() {
+        let mut cv = CircularVec::new("a".to_string(), vec!["b".to_string()]);
+        cv.next();
+        let json = serde_json::to_string(&cv).unwrap();
+        assert_eq!(json, r#"{"items":["a","b"],"index":1}"#);
+
+        let mut back: CircularVec<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, cv);
+        assert_eq!(back.next(), "b");
+    }
+
+    #[test]
+    fn round_trip_keeps_direction() {
+        let mut cv = CircularVec::from([1, 2, 3]).with_traversal_mode(TraversalMode::Bounce);
+        cv.skip(3);
+        let json = serde_json::to_string(&cv).unwrap();
+        assert_eq!(
+            json,
+            r#"{"items":[1,2,3],"index":1,"mode":"Bounce","direction":"Backward"}"#
+        );
+
+        let mut back: CircularVec<u8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, cv);
+        assert_eq!(back.next(), &2);
+        assert_eq!(back.next(), &1);
+        assert_eq!(back.next(), &2);
+    }
+
+    #[test]
+    fn round_trip_keeps_stride() {
+        let cv = CircularVec::from([1, 2, 3]).with_stride(-1);
+        let json = serde_json::to_string(&cv).unwrap();
+        assert_eq!(json, r#"{"items":[1,2,3],"index":0,"stride":-1}"#);
+
+        let mut back: CircularVec<u8> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.next(), &1);
+        assert_eq!(back.next(), &3);
+    }
+
+    #[test]
+    fn reject_invalid() {
+        let err = serde_json::from_str::<CircularVec<u8>>(r#"{"items":[],"index":0}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at least one item"), "{}", err);
+
+        let err = serde_json::from_str::<CircularVec<u8>>(r#"{"items":[1,2],"index":2}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("cursor index 2 is out of bounds for 2 items"),
+            "{}",
+            err
+        );
+
+        let err = serde_json::from_str::<CircularVec<u8>>(r#"{"items":[1],"index":0,"stride":0}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("stride must not be zero"), "{}", err);
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Config {
+        #[serde(with = "items_only")]
+        backends: CircularVec<u16>,
+    }
+
+    #[test]
+    fn items_only_representation() {
+        let mut backends = CircularVec::from([80, 443]);
+        backends.next();
+        let json = serde_json::to_string(&Config { backends }).unwrap();
+        assert_eq!(json, r#"{"backends":[80,443]}"#);
+
+        let config: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.backends.position().index, 0);
+        assert!(serde_json::from_str::<Config>(r#"{"backends":[]}"#).is_err());
+    }
+}
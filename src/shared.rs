@@ -0,0 +1,156 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::wrap;
+use crate::{CircularVec, CircularVecError};
+
+/// A CircularVec whose cursor is an atomic counter, so `next` only needs
+/// `&self` and many threads can pick items round-robin at the same time
+/// without a lock. Share it between threads or tasks with an `Arc`.
+///
+/// The items cannot be changed once the SharedCircularVec is built.
+///
+/// # Fairness
+///
+/// Every call to `next` claims the cursor position with a single atomic
+/// compare-and-swap, so the calls that succeed form one round-robin
+/// sequence no matter how many threads are involved: across any `k` full
+/// laps' worth of consecutive calls, each item is returned exactly `k`
+/// times. Which thread gets which item depends on scheduling. Under heavy
+/// contention a call may have to retry its compare-and-swap, but some
+/// call always succeeds, so the selector as a whole never stalls.
+pub struct SharedCircularVec<T> {
+    items: Vec<T>,
+    index: AtomicUsize,
+}
+
+impl<T> SharedCircularVec<T> {
+    /// Create a SharedCircularVec from a first item and any number of
+    /// further items. Because the first item is required, this cannot fail.
+    pub fn new<I: IntoIterator<Item = T>>(first: T, rest: I) -> Self {
+        CircularVec::new(first, rest).into()
+    }
+
+    /// Create a SharedCircularVec from an iterator, returning
+    /// `CircularVecError::Empty` if the iterator yields no items.
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, CircularVecError> {
+        CircularVec::try_from_iter(iter).map(Self::from)
+    }
+
+    /// Get an immutable reference to the next item, moving the shared
+    /// cursor on by one.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&self) -> &T {
+        let len = self.items.len();
+        // The closure always returns Some, so this never fails.
+        let index = match self
+            .index
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |i| {
+                Some(wrap::increment(i, len))
+            }) {
+            Ok(i) | Err(i) => i,
+        };
+        &self.items[index]
+    }
+
+    /// Get an immutable reference to the item `next` would return if it
+    /// were called now. Other threads may move the cursor at any time, so
+    /// this is only a snapshot.
+    pub fn peek(&self) -> &T {
+        &self.items[self.position()]
+    }
+
+    /// The index of the item `next` would return if it were called now.
+    pub fn position(&self) -> usize {
+        self.index.load(Ordering::Relaxed)
+    }
+
+    /// Convert back into a CircularVec, keeping the cursor position.
+    /// Every item starts out enabled.
+    pub fn into_circular_vec(self) -> CircularVec<T> {
+        CircularVec::from_parts(self.items, self.index.into_inner())
+    }
+}
+
+/// Keeps the index of the CircularVec's cursor. Enabled flags, the
+/// traversal mode, the stride, the lap count and wrap hooks are dropped,
+/// because SharedCircularVec returns every item in order, always wraps
+/// and does not count laps.
+impl<T> From<CircularVec<T>> for SharedCircularVec<T> {
+    fn from(cv: CircularVec<T>) -> Self {
+        SharedCircularVec {
+            items: cv.items,
+            index: AtomicUsize::new(cv.cursor.index),
+        }
+    }
+}
+
+impl<T> TryFrom<Vec<T>> for SharedCircularVec<T> {
+    type Error = CircularVecError;
+
+    fn try_from(items: Vec<T>) -> Result<Self, Self::Error> {
+        CircularVec::try_from(items).map(Self::from)
+    }
+}
+
+impl<T> Deref for SharedCircularVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SharedCircularVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedCircularVec")
+            .field("items", &self.items)
+            .field("index", &self.position())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use std::thread;
+
+    #[test]
+    fn loop_through() {
+        let shared = SharedCircularVec::new("a", vec!["b", "c"]);
+        assert_eq!(shared.next(), &"a");
+        assert_eq!(shared.peek(), &"b");
+        assert_eq!(shared.next(), &"b");
+        assert_eq!(shared.next(), &"c");
+        assert_eq!(shared.next(), &"a");
+        assert_eq!(shared.into_circular_vec().position().index, 1);
+        assert!(SharedCircularVec::<u8>::try_from(vec![]).is_err());
+    }
+
+    #[test]
+    fn concurrent_round_robin_is_exact() {
+        const THREADS: usize = 8;
+        const CALLS: usize = 7_000;
+        let shared = SharedCircularVec::try_from_iter(0..7).unwrap();
+        let counts: Vec<AtomicUsize> = (0..7).map(|_| AtomicUsize::new(0)).collect();
+
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    for _ in 0..CALLS {
+                        counts[*shared.next()].fetch_add(1, Ordering::Relaxed);
+                    }
+                });
+            }
+        });
+
+        for count in &counts {
+            assert_eq!(count.load(Ordering::Relaxed), THREADS * CALLS / 7);
+        }
+        assert_eq!(shared.position(), THREADS * CALLS % 7);
+    }
+}
@@ -0,0 +1,188 @@
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngExt, SeedableRng};
+
+use crate::CircularVecError;
+
+/// A circular collection that visits every item once per lap, in a random
+/// order that is drawn afresh each time a lap ends.
+///
+/// The item that ends one lap is never the item that starts the next, so
+/// `next` only returns the same item twice in a row when there is just
+/// one item.
+///
+/// The random number generator is a type parameter so it can be seeded:
+/// two ShuffledCircularVecs built from the same items and the same seed
+/// return the same sequence. `StdRng` is only reproducible for a given
+/// version of the `rand` crate; pass a generator with a stable algorithm
+/// to `with_rng` if sequences must survive dependency upgrades.
+///
+///     # use circular_vec::ShuffledCircularVec;
+///     let mut fixtures = ShuffledCircularVec::seed_from_u64(vec!["a", "b", "c"], 42).unwrap();
+///     let mut lap: Vec<_> = (0..3).map(|_| *fixtures.next()).collect();
+///     lap.sort();
+///     assert_eq!(lap, ["a", "b", "c"]);
+#[derive(Clone, Debug)]
+pub struct ShuffledCircularVec<T, R = StdRng> {
+    items: Vec<T>,
+    // A permutation of the indexes of `items`: the order of this lap.
+    order: Vec<usize>,
+    // Index into `order` of the next item. Equal to `order.len()` once
+    // the lap is used up.
+    position: usize,
+    // Index into `items` of the item `next` returned last, which the
+    // next lap must not start with.
+    last: Option<usize>,
+    rng: R,
+}
+
+impl<T> ShuffledCircularVec<T, StdRng> {
+    /// Create a ShuffledCircularVec whose order is driven by a `StdRng`
+    /// seeded with `seed`. Returns `CircularVecError::Empty` if there are
+    /// no items.
+    pub fn seed_from_u64<I: IntoIterator<Item = T>>(
+        items: I,
+        seed: u64,
+    ) -> Result<Self, CircularVecError> {
+        Self::with_rng(items, StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<T, R: Rng> ShuffledCircularVec<T, R> {
+    /// Create a ShuffledCircularVec whose order is driven by `rng`.
+    /// Returns `CircularVecError::Empty` if there are no items.
+    pub fn with_rng<I: IntoIterator<Item = T>>(
+        items: I,
+        mut rng: R,
+    ) -> Result<Self, CircularVecError> {
+        let items: Vec<T> = items.into_iter().collect();
+        if items.is_empty() {
+            return Err(CircularVecError::Empty);
+        }
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.shuffle(&mut rng);
+        Ok(ShuffledCircularVec {
+            items,
+            order,
+            position: 0,
+            last: None,
+            rng,
+        })
+    }
+
+    /// Get an immutable reference to the next item of this lap, starting
+    /// a newly shuffled lap first if this one is used up.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &T {
+        let index = self.next_index();
+        &self.items[index]
+    }
+
+    /// Get a mutable reference to the next item of this lap, starting a
+    /// newly shuffled lap first if this one is used up.
+    pub fn next_mut(&mut self) -> &mut T {
+        let index = self.next_index();
+        &mut self.items[index]
+    }
+
+    /// Abandon the rest of this lap and start a newly shuffled one.
+    pub fn reshuffle(&mut self) {
+        self.order.shuffle(&mut self.rng);
+        self.position = 0;
+        let len = self.order.len();
+        if len > 1 && Some(self.order[0]) == self.last {
+            let swap_with = self.rng.random_range(1..len);
+            self.order.swap(0, swap_with);
+        }
+    }
+
+    /// How many items are left before this lap ends. The next lap is
+    /// only shuffled when `next` is called after this reaches zero.
+    pub fn remaining_in_lap(&self) -> usize {
+        self.order.len() - self.position
+    }
+
+    fn next_index(&mut self) -> usize {
+        if self.position == self.order.len() {
+            self.reshuffle();
+        }
+        let index = self.order[self.position];
+        self.position += 1;
+        self.last = Some(index);
+        index
+    }
+}
+
+impl<T, R> Deref for ShuffledCircularVec<T, R> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn every_lap_is_a_permutation() {
+        let mut scv = ShuffledCircularVec::seed_from_u64(0..10, 7).unwrap();
+        let mut previous = None;
+        assert_eq!(scv.remaining_in_lap(), 10);
+        for _ in 0..200 {
+            let mut lap: Vec<i32> = (0..10).map(|_| *scv.next()).collect();
+            assert_eq!(scv.remaining_in_lap(), 0);
+            assert_ne!(previous, Some(lap[0]));
+            previous = Some(lap[9]);
+            lap.sort();
+            assert_eq!(lap, (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn no_repeat_after_reshuffle() {
+        let mut scv = ShuffledCircularVec::seed_from_u64(vec!['a', 'b'], 1).unwrap();
+        for _ in 0..100 {
+            let last = *scv.next();
+            scv.reshuffle();
+            assert_ne!(*scv.next(), last);
+        }
+    }
+
+    #[test]
+    fn no_repeat_after_repeated_reshuffles() {
+        for seed in 0..200 {
+            let mut scv = ShuffledCircularVec::seed_from_u64(0..3, seed).unwrap();
+            let last = *scv.next();
+            scv.reshuffle();
+            scv.reshuffle();
+            assert_ne!(*scv.next(), last, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = ShuffledCircularVec::seed_from_u64(0..5, 99).unwrap();
+        let mut b = ShuffledCircularVec::seed_from_u64(0..5, 99).unwrap();
+        let seq_a: Vec<i32> = (0..50).map(|_| *a.next()).collect();
+        let seq_b: Vec<i32> = (0..50).map(|_| *b.next()).collect();
+        assert_eq!(seq_a, seq_b);
+    }
+
+    #[test]
+    fn single_item() {
+        let mut scv = ShuffledCircularVec::seed_from_u64(vec![1], 0).unwrap();
+        *scv.next_mut() += 1;
+        assert_eq!(scv.next(), &2);
+        assert_eq!(scv.next(), &2);
+        assert_eq!(
+            ShuffledCircularVec::seed_from_u64(Vec::<u8>::new(), 0).err(),
+            Some(CircularVecError::Empty)
+        );
+    }
+}
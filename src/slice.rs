@@ -0,0 +1,383 @@
+//! Views of a run of consecutive items that may wrap around the end of
+//! a CircularVec.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::{Chain, FusedIterator};
+use core::ops::{Index, IndexMut};
+use core::slice;
+
+/// A view of consecutive items of a CircularVec, where the run may cross
+/// from the last item back to the first. Created by
+/// `CircularVec::range_wrapping`.
+///
+/// The items are held as two slices: the run up to the end of the
+/// storage, then the rest of it from the start. The second slice is
+/// empty unless the run wraps.
+///
+///     # use circular_vec::CircularVec;
+///     let cv = CircularVec::from([1, 2, 3, 4]);
+///     let window = cv.range_wrapping(3, 3);
+///     assert_eq!(window.as_slices(), (&[4][..], &[1, 2][..]));
+///     assert_eq!(window[1], 1);
+///     assert_eq!(window.to_vec(), [4, 1, 2]);
+pub struct CircularSlice<'a, T> {
+    first: &'a [T],
+    second: &'a [T],
+}
+
+impl<'a, T> CircularSlice<'a, T> {
+    pub(crate) fn new(first: &'a [T], second: &'a [T]) -> Self {
+        CircularSlice { first, second }
+    }
+
+    /// The items as two slices which, concatenated, make up the view.
+    pub fn as_slices(&self) -> (&'a [T], &'a [T]) {
+        (self.first, self.second)
+    }
+
+    /// The number of items in the view.
+    pub fn len(&self) -> usize {
+        self.first.len() + self.second.len()
+    }
+
+    /// Whether the view has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get an immutable reference to the item at `index` within the view,
+    /// or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        match index.checked_sub(self.first.len()) {
+            None => self.first.get(index),
+            Some(index) => self.second.get(index),
+        }
+    }
+
+    /// Iterate over the items of the view in order.
+    pub fn iter(&self) -> Iter<'a, T> {
+        Iter {
+            inner: self.first.iter().chain(self.second.iter()),
+            remaining: self.len(),
+        }
+    }
+
+    /// Copy the items of the view into a new Vec.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut items = Vec::with_capacity(self.len());
+        items.extend_from_slice(self.first);
+        items.extend_from_slice(self.second);
+        items
+    }
+}
+
+// Derived Clone and Copy would needlessly require `T: Clone`.
+impl<'a, T> Clone for CircularSlice<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for CircularSlice<'a, T> {}
+
+impl<'a, T: fmt::Debug> fmt::Debug for CircularSlice<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+impl<'a, T> Index<usize> for CircularSlice<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(item) => item,
+            None => out_of_bounds(index, self.len()),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for CircularSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &CircularSlice<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A mutable view of consecutive items of a CircularVec, where the run
+/// may cross from the last item back to the first. Created by
+/// `CircularVec::range_wrapping_mut`. See `CircularSlice`.
+pub struct CircularSliceMut<'a, T> {
+    first: &'a mut [T],
+    second: &'a mut [T],
+}
+
+impl<'a, T> CircularSliceMut<'a, T> {
+    pub(crate) fn new(first: &'a mut [T], second: &'a mut [T]) -> Self {
+        CircularSliceMut { first, second }
+    }
+
+    /// The items as two slices which, concatenated, make up the view.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        (self.first, self.second)
+    }
+
+    /// The items as two mutable slices which, concatenated, make up the
+    /// view.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        (self.first, self.second)
+    }
+
+    /// An immutable view of the same items.
+    pub fn as_circular_slice(&self) -> CircularSlice<'_, T> {
+        CircularSlice::new(self.first, self.second)
+    }
+
+    /// The number of items in the view.
+    pub fn len(&self) -> usize {
+        self.first.len() + self.second.len()
+    }
+
+    /// Whether the view has no items.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get an immutable reference to the item at `index` within the view,
+    /// or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_circular_slice().get(index)
+    }
+
+    /// Get a mutable reference to the item at `index` within the view,
+    /// or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        match index.checked_sub(self.first.len()) {
+            None => self.first.get_mut(index),
+            Some(index) => self.second.get_mut(index),
+        }
+    }
+
+    /// Iterate over the items of the view in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.as_circular_slice().iter()
+    }
+
+    /// Iterate mutably over the items of the view in order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            remaining: self.len(),
+            inner: self.first.iter_mut().chain(self.second.iter_mut()),
+        }
+    }
+
+    /// Copy the items of the view into a new Vec.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.as_circular_slice().to_vec()
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for CircularSliceMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+impl<'a, T> Index<usize> for CircularSliceMut<'a, T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.get(index) {
+            Some(item) => item,
+            None => out_of_bounds(index, self.len()),
+        }
+    }
+}
+
+/// # Panics
+///
+/// Panics if `index` is out of bounds.
+impl<'a, T> IndexMut<usize> for CircularSliceMut<'a, T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(item) => item,
+            None => out_of_bounds(index, len),
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for CircularSliceMut<'a, T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IterMut {
+            remaining: self.first.len() + self.second.len(),
+            inner: self.first.iter_mut().chain(self.second.iter_mut()),
+        }
+    }
+}
+
+fn out_of_bounds(index: usize, len: usize) -> ! {
+    panic!(
+        "index out of bounds: the len is {} but the index is {}",
+        len, index
+    )
+}
+
+/// Iterator over the items of a `CircularSlice` or `CircularSliceMut`.
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+    inner: Chain<slice::Iter<'a, T>, slice::Iter<'a, T>>,
+    remaining: usize,
+}
+
+// Derived Clone would needlessly require `T: Clone`.
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            inner: self.inner.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+/// Mutable iterator over the items of a `CircularSliceMut`.
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+    inner: Chain<slice::IterMut<'a, T>, slice::IterMut<'a, T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CircularVec;
+    use alloc::format;
+    use alloc::vec;
+
+    #[test]
+    fn wrapped_view() {
+        let cv = CircularVec::new(1, vec![2, 3, 4, 5]);
+        let window = cv.range_wrapping(3, 4);
+        assert_eq!(window.len(), 4);
+        assert_eq!(window.as_slices(), (&[4, 5][..], &[1, 2][..]));
+        assert_eq!(window.iter().copied().collect::<Vec<_>>(), [4, 5, 1, 2]);
+        assert_eq!(window.iter().next_back(), Some(&2));
+        assert_eq!((window[0], window[3]), (4, 2));
+        assert_eq!(window.get(4), None);
+        assert_eq!(format!("{:?}", window), "[4, 5, 1, 2]");
+
+        let inside = cv.range_wrapping(6, 3);
+        assert_eq!(inside.as_slices(), (&[2, 3, 4][..], &[][..]));
+        assert!(cv.range_wrapping(2, 0).is_empty());
+        assert_eq!(cv.range_wrapping(1, 5).to_vec(), [2, 3, 4, 5, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the len is 2 but the index is 2")]
+    fn index_out_of_view() {
+        let cv = CircularVec::from([1, 2, 3]);
+        let _ = cv.range_wrapping(2, 2)[2];
+    }
+
+    #[test]
+    #[should_panic(expected = "longer than the 3 items")]
+    fn view_too_long() {
+        CircularVec::from([1, 2, 3]).range_wrapping(0, 4);
+    }
+
+    #[test]
+    fn mutable_view() {
+        let mut cv = CircularVec::new(1, vec![2, 3, 4]);
+        let mut window = cv.range_wrapping_mut(2, 3);
+        window[0] *= 10;
+        *window.get_mut(2).unwrap() *= 10;
+        for item in window.iter_mut() {
+            *item += 1;
+        }
+        assert_eq!(window.to_vec(), [31, 5, 11]);
+        window.as_mut_slices().1[0] = 0;
+        for item in window {
+            *item += 1;
+        }
+        assert_eq!(cv.as_ref(), [1, 2, 32, 6]);
+    }
+}
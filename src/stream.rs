@@ -0,0 +1,376 @@
+//! An async Stream that returns a CircularVec's items on a schedule.
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use core::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use futures_core::Stream;
+use tokio::time::{self, Instant, Interval};
+
+use crate::CircularVec;
+
+/// A Stream that yields a clone of the CircularVec's next item on every
+/// tick of a Tokio interval. Created by `CircularVec::into_stream` or
+/// `CircularVec::stream_with_interval`.
+///
+/// Items are cloned as they are yielded, so for large items store them
+/// in an `Arc` to make that cheap. The stream never ends. A tick on which
+/// every item is disabled yields nothing, and the stream waits for the
+/// next tick.
+///
+/// The stream can be paused, resumed and given a new interval while it
+/// is running, either directly or from another task through a
+/// `StreamHandle`.
+///
+///     # use circular_vec::CircularVec;
+///     # use std::time::Duration;
+///     # async fn rotate_banners() {
+///     let banners = CircularVec::from(["sale", "new arrivals"]);
+///     let stream = banners.into_stream(Duration::from_secs(30));
+///     // Hand the handle to whatever needs to pause the rotation, then
+///     // poll `stream` for a banner every 30 seconds.
+///     let handle = stream.handle();
+///     handle.pause();
+///     # }
+pub struct ItemStream<T> {
+    // Boxed so that the stream is `Unpin` whatever `T` is. The items are
+    // never pinned: `poll_next` only hands out clones.
+    items: Box<CircularVec<T>>,
+    interval: Interval,
+    shared: Arc<Shared>,
+}
+
+/// A cloneable handle that controls an `ItemStream` from anywhere,
+/// including other tasks and threads.
+#[derive(Clone, Debug)]
+pub struct StreamHandle {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    control: Mutex<Control>,
+}
+
+/// Requests from the handles, applied the next time the stream is
+/// polled.
+#[derive(Debug, Default)]
+struct Control {
+    paused: bool,
+    resumed: bool,
+    period: Option<Duration>,
+    // The task that last polled the stream, woken when anything changes
+    // so that a pause, resume or new interval takes effect straight away
+    // rather than at the next tick.
+    waker: Option<Waker>,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Control> {
+        // Nothing can panic while the lock is held, but recover anyway
+        // rather than poisoning every handle.
+        self.control.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update<F: FnOnce(&mut Control)>(&self, f: F) {
+        let mut control = self.lock();
+        f(&mut control);
+        if let Some(waker) = control.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl StreamHandle {
+    /// Stop the stream from yielding items until `resume` is called.
+    pub fn pause(&self) {
+        self.shared.update(|control| control.paused = true);
+    }
+
+    /// Let a paused stream yield items again. The next item comes one full
+    /// interval after the stream is next polled, rather than all at once
+    /// for the ticks missed while paused. Resuming a stream that is not
+    /// paused has no effect.
+    pub fn resume(&self) {
+        self.shared.update(|control| {
+            if control.paused {
+                control.paused = false;
+                control.resumed = true;
+            }
+        });
+    }
+
+    /// Whether the stream is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.lock().paused
+    }
+
+    /// Change how often the stream yields an item. The next item comes
+    /// one new interval after the stream is next polled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_interval(&self, period: Duration) {
+        assert!(period > Duration::ZERO, "the interval must not be zero");
+        self.shared.update(|control| control.period = Some(period));
+    }
+}
+
+impl<T> ItemStream<T> {
+    fn new(items: CircularVec<T>, interval: Interval) -> Self {
+        ItemStream {
+            items: Box::new(items),
+            interval,
+            shared: Arc::default(),
+        }
+    }
+
+    /// A handle for pausing, resuming and changing the interval of this
+    /// stream from elsewhere.
+    pub fn handle(&self) -> StreamHandle {
+        StreamHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Stop the stream from yielding items until `resume` is called.
+    pub fn pause(&self) {
+        self.handle().pause();
+    }
+
+    /// Let a paused stream yield items again. See `StreamHandle::resume`.
+    pub fn resume(&self) {
+        self.handle().resume();
+    }
+
+    /// Whether the stream is paused.
+    pub fn is_paused(&self) -> bool {
+        self.shared.lock().paused
+    }
+
+    /// Change how often the stream yields an item. See
+    /// `StreamHandle::set_interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn set_interval(&self, period: Duration) {
+        self.handle().set_interval(period);
+    }
+
+    /// How often the stream yields an item.
+    pub fn interval(&self) -> Duration {
+        self.shared.lock().period.unwrap_or(self.interval.period())
+    }
+
+    /// The CircularVec the items come from.
+    pub fn get_ref(&self) -> &CircularVec<T> {
+        &self.items
+    }
+
+    /// The CircularVec the items come from, for example to disable some
+    /// of them or move the cursor.
+    pub fn get_mut(&mut self) -> &mut CircularVec<T> {
+        &mut self.items
+    }
+
+    /// Stop streaming and get the CircularVec back, with its cursor just
+    /// past the last item yielded.
+    pub fn into_inner(self) -> CircularVec<T> {
+        *self.items
+    }
+
+    /// Apply requests from the handles, and arrange for the task to be
+    /// woken when there are new ones. Returns false if the stream is
+    /// paused.
+    fn apply_control(&mut self, cx: &mut Context<'_>) -> bool {
+        let mut control = self.shared.lock();
+        if !control
+            .waker
+            .as_ref()
+            .is_some_and(|w| w.will_wake(cx.waker()))
+        {
+            control.waker = Some(cx.waker().clone());
+        }
+        if let Some(period) = control.period.take() {
+            let behavior = self.interval.missed_tick_behavior();
+            self.interval = time::interval_at(Instant::now() + period, period);
+            self.interval.set_missed_tick_behavior(behavior);
+        }
+        if control.paused {
+            return false;
+        }
+        if control.resumed {
+            control.resumed = false;
+            self.interval.reset();
+        }
+        true
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ItemStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ItemStream")
+            .field("items", &self.items)
+            .field("interval", &self.interval())
+            .field("paused", &self.is_paused())
+            .finish()
+    }
+}
+
+impl<T: Clone> Stream for ItemStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = Pin::into_inner(self);
+        loop {
+            if !this.apply_control(cx) {
+                return Poll::Pending;
+            }
+            if this.interval.poll_tick(cx).is_pending() {
+                return Poll::Pending;
+            }
+            if let Ok(item) = this.items.try_next() {
+                return Poll::Ready(Some(item.clone()));
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl<T> CircularVec<T> {
+    /// Turn the CircularVec into a Stream that yields a clone of the next
+    /// item straight away and then once every `period`. See `ItemStream`.
+    ///
+    /// Must be called from within a Tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn into_stream(self, period: Duration) -> ItemStream<T> {
+        self.stream_with_interval(time::interval(period))
+    }
+
+    /// Like `into_stream`, but driven by the given Tokio interval, so its
+    /// first tick, period and missed tick behavior decide when items are
+    /// yielded.
+    pub fn stream_with_interval(self, interval: Interval) -> ItemStream<T> {
+        ItemStream::new(self, interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use core::future::poll_fn;
+    use core::marker::PhantomPinned;
+
+    async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    async fn next_within<S: Stream + Unpin>(stream: &mut S, millis: u64) -> Option<S::Item> {
+        time::timeout(Duration::from_millis(millis), next(stream))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn yields_on_each_tick() {
+        let start = Instant::now();
+        let mut stream = CircularVec::new("a", vec!["b", "c"]).into_stream(Duration::from_secs(1));
+        assert_eq!(next(&mut stream).await, Some("a"));
+        assert_eq!(next(&mut stream).await, Some("b"));
+        assert_eq!(next(&mut stream).await, Some("c"));
+        assert_eq!(next(&mut stream).await, Some("a"));
+        assert_eq!(start.elapsed(), Duration::from_secs(3));
+        assert_eq!(stream.into_inner().position().index, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pause_and_resume() {
+        let mut stream = CircularVec::from([1, 2, 3]).into_stream(Duration::from_secs(1));
+        let handle = stream.handle();
+        assert_eq!(next(&mut stream).await, Some(1));
+
+        handle.pause();
+        assert!(stream.is_paused());
+        assert_eq!(next_within(&mut stream, 5_000).await, None);
+
+        let resumed = Instant::now();
+        handle.resume();
+        assert_eq!(next(&mut stream).await, Some(2));
+        assert_eq!(resumed.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn resume_wakes_waiting_task() {
+        let mut stream = CircularVec::from([1, 2]).into_stream(Duration::from_millis(10));
+        let handle = stream.handle();
+        handle.pause();
+        let consumer = tokio::spawn(async move { next(&mut stream).await });
+        time::sleep(Duration::from_secs(1)).await;
+        handle.resume();
+        assert_eq!(consumer.await.unwrap(), Some(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn change_interval() {
+        let mut stream = CircularVec::from([1, 2, 3]).into_stream(Duration::from_secs(10));
+        assert_eq!(next(&mut stream).await, Some(1));
+
+        stream.set_interval(Duration::from_secs(2));
+        assert_eq!(stream.interval(), Duration::from_secs(2));
+        let changed = Instant::now();
+        assert_eq!(next(&mut stream).await, Some(2));
+        assert_eq!(next(&mut stream).await, Some(3));
+        assert_eq!(changed.elapsed(), Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn change_interval_while_waiting() {
+        let mut stream = CircularVec::from([1, 2]).into_stream(Duration::from_secs(3600));
+        assert_eq!(next(&mut stream).await, Some(1));
+
+        let handle = stream.handle();
+        let changed = Instant::now();
+        let consumer = tokio::spawn(async move { next(&mut stream).await });
+        time::sleep(Duration::from_secs(1)).await;
+        handle.set_interval(Duration::from_secs(1));
+        assert_eq!(consumer.await.unwrap(), Some(2));
+        assert_eq!(changed.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unpin_for_any_item() {
+        fn assert_unpin<S: Unpin>(_: &S) {}
+        let stream = CircularVec::new(PhantomPinned, None).into_stream(Duration::from_secs(1));
+        assert_unpin(&stream);
+    }
+
+    #[tokio::test(start_paused = true)]
+    #[should_panic(expected = "the interval must not be zero")]
+    async fn reject_zero_interval() {
+        let stream = CircularVec::from([1]).into_stream(Duration::from_secs(1));
+        stream.handle().set_interval(Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_ticks_while_all_disabled() {
+        let mut stream = CircularVec::from([1, 2]).into_stream(Duration::from_secs(1));
+        stream.get_mut().disable(0);
+        stream.get_mut().disable(1);
+        assert_eq!(next_within(&mut stream, 2_500).await, None);
+        stream.get_mut().enable(1);
+        assert_eq!(next(&mut stream).await, Some(2));
+    }
+}
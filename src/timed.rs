@@ -0,0 +1,323 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Deref;
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+use crate::{CircularVec, CircularVecError};
+
+/// A circular collection where each item stays current for its own
+/// duration before handing over to the next, like the frames of a sprite
+/// animation or the phases of a traffic signal.
+///
+/// The schedule starts when the TimedCircularVec is created and then
+/// repeats forever. `current_at` works out which item is current at any
+/// given time. `current` asks the clock for the time and also moves an
+/// inner CircularVec's cursor on to the current item, so the lap count
+/// and wrap hooks work the same way as they do for `next`. Items with a
+/// duration of zero are never current.
+///
+/// The clock is a type parameter so tests can use a `ManualClock`.
+///
+///     # use circular_vec::{ManualClock, TimedCircularVec};
+///     # use std::time::{Duration, Instant};
+///     let clock = ManualClock::new(Instant::now());
+///     let mut signal = TimedCircularVec::with_clock(
+///         vec![
+///             ("red", Duration::from_secs(30)),
+///             ("green", Duration::from_secs(25)),
+///             ("amber", Duration::from_secs(5)),
+///         ],
+///         clock.clone(),
+///     )
+///     .unwrap();
+///
+///     assert_eq!(signal.current(), &"red");
+///     clock.advance(Duration::from_secs(57));
+///     assert_eq!(signal.current(), &"amber");
+///     clock.advance(Duration::from_secs(3));
+///     assert_eq!(signal.current(), &"red");
+///     assert_eq!(signal.laps_completed(), 1);
+pub struct TimedCircularVec<T, C = SystemClock> {
+    items: CircularVec<T>,
+    // Running totals of the durations: item `i` is current from
+    // `ends[i - 1]` until `ends[i]` into each cycle.
+    ends: Vec<Duration>,
+    start: Instant,
+    // The most items the cursor of `items` has been seen to move past
+    // since `start`. Only moving beyond this counts laps, so a clock that
+    // goes backwards and forwards again does not count them twice.
+    steps: u128,
+    clock: C,
+}
+
+impl<T> TimedCircularVec<T, SystemClock> {
+    /// Create a TimedCircularVec from `(item, duration)` pairs, timed by
+    /// the system clock. The first item is current from now.
+    ///
+    /// Returns `CircularVecError::Empty` if there are no pairs, or
+    /// `CircularVecError::ZeroTotalDuration` if every duration is zero.
+    pub fn try_from_iter<I: IntoIterator<Item = (T, Duration)>>(
+        iter: I,
+    ) -> Result<Self, CircularVecError> {
+        Self::with_clock(iter, SystemClock)
+    }
+}
+
+impl<T, C: Clock> TimedCircularVec<T, C> {
+    /// Create a TimedCircularVec from `(item, duration)` pairs, timed by
+    /// `clock`. The first item is current from the clock's current time.
+    ///
+    /// Returns `CircularVecError::Empty` if there are no pairs, or
+    /// `CircularVecError::ZeroTotalDuration` if every duration is zero.
+    pub fn with_clock<I: IntoIterator<Item = (T, Duration)>>(
+        iter: I,
+        clock: C,
+    ) -> Result<Self, CircularVecError> {
+        let (items, durations): (Vec<T>, Vec<Duration>) = iter.into_iter().unzip();
+        let items = CircularVec::try_from(items)?;
+        let ends: Vec<Duration> = durations
+            .iter()
+            .scan(Duration::ZERO, |total, &duration| {
+                *total = total.saturating_add(duration);
+                Some(*total)
+            })
+            .collect();
+        if ends.last() == Some(&Duration::ZERO) {
+            return Err(CircularVecError::ZeroTotalDuration);
+        }
+        Ok(TimedCircularVec {
+            items,
+            ends,
+            start: clock.now(),
+            steps: 0,
+            clock,
+        })
+    }
+
+    /// Get an immutable reference to the item that is current now, moving
+    /// the cursor on to it and counting any laps completed since the last
+    /// call.
+    pub fn current(&mut self) -> &T {
+        let (steps, index, _) = self.locate(self.clock.now());
+        if steps > self.steps {
+            // The cursor may have followed the clock backwards, so put it
+            // back where `self.steps` left it before moving on from there.
+            let len = self.ends.len() as u128;
+            self.items.set_position((self.steps % len) as usize);
+            let mut behind = steps - self.steps;
+            while behind > 0 {
+                let skip = usize::try_from(behind).unwrap_or(usize::MAX);
+                self.items.skip(skip);
+                behind -= skip as u128;
+            }
+            self.steps = steps;
+        } else {
+            // The clock went backwards, which only a ManualClock can do,
+            // or has not moved on to the next item. Follow it without
+            // counting any laps.
+            self.items.set_position(index);
+        }
+        self.items.peek()
+    }
+
+    /// How long the item that is current now stays current.
+    pub fn time_remaining(&self) -> Duration {
+        self.remaining_at(self.clock.now())
+    }
+
+    /// Start the schedule again from the first item, as of now.
+    pub fn restart(&mut self) {
+        self.start = self.clock.now();
+        self.steps = 0;
+        self.items.reset();
+    }
+}
+
+impl<T, C> TimedCircularVec<T, C> {
+    /// Get an immutable reference to the item that is current at `at`.
+    /// Times before the schedule started count as the start. This does
+    /// not move the cursor or read the clock.
+    pub fn current_at(&self, at: Instant) -> &T {
+        let (_, index, _) = self.locate(at);
+        &self.items[index]
+    }
+
+    /// How long the item that is current at `at` stays current after it.
+    pub fn remaining_at(&self, at: Instant) -> Duration {
+        let (_, _, remaining) = self.locate(at);
+        remaining
+    }
+
+    /// How long the item at `index` stays current each cycle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn duration(&self, index: usize) -> Duration {
+        match index {
+            0 => self.ends[0],
+            _ => self.ends[index] - self.ends[index - 1],
+        }
+    }
+
+    /// The length of one full cycle through the items.
+    pub fn total_duration(&self) -> Duration {
+        self.ends[self.ends.len() - 1]
+    }
+
+    /// How many full cycles `current` has seen completed.
+    pub fn laps_completed(&self) -> u64 {
+        self.items.laps_completed()
+    }
+
+    /// Register a hook to run each time `current` sees a cycle completed.
+    /// See `CircularVec::on_wrap`.
+    pub fn on_wrap<F: FnMut(u64) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.items.on_wrap(hook);
+    }
+
+    /// The number of items moved past since the schedule started, the
+    /// index of the item current at `at`, and how long it stays current.
+    fn locate(&self, at: Instant) -> (u128, usize, Duration) {
+        let elapsed = at.saturating_duration_since(self.start).as_nanos();
+        let total = self.total_duration().as_nanos();
+        let offset = elapsed % total;
+        // Never out of bounds, because `offset` is below the last end.
+        let index = self.ends.partition_point(|end| end.as_nanos() <= offset);
+        let cycles = elapsed / total;
+        let steps = cycles * self.ends.len() as u128 + index as u128;
+        let remaining = from_nanos(self.ends[index].as_nanos() - offset);
+        (steps, index, remaining)
+    }
+}
+
+fn from_nanos(nanos: u128) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    Duration::new(
+        (nanos / NANOS_PER_SEC) as u64,
+        (nanos % NANOS_PER_SEC) as u32,
+    )
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for TimedCircularVec<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedCircularVec")
+            .field("items", &self.items)
+            .field("ends", &self.ends)
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+impl<T, C> Deref for TimedCircularVec<T, C> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use alloc::vec;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn frames(clock: &ManualClock) -> TimedCircularVec<char, ManualClock> {
+        TimedCircularVec::with_clock(
+            vec![
+                ('a', secs(2)),
+                ('b', secs(0)),
+                ('c', secs(1)),
+                ('d', secs(3)),
+            ],
+            clock.clone(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn current_at_follows_durations() {
+        let start = Instant::now();
+        let tcv = frames(&ManualClock::new(start));
+        let at = |s: u64| *tcv.current_at(start + secs(s));
+        let seen: Vec<char> = (0..13).map(at).collect();
+        assert_eq!(seen, "aacdddaacddda".chars().collect::<Vec<_>>());
+        assert_eq!(*tcv.current_at(start - secs(1)), 'a');
+        assert_eq!(
+            tcv.remaining_at(start + Duration::from_millis(3500)),
+            Duration::from_millis(2500)
+        );
+        assert_eq!(tcv.total_duration(), secs(6));
+        assert_eq!((tcv.duration(1), tcv.duration(3)), (secs(0), secs(3)));
+    }
+
+    #[test]
+    fn current_moves_cursor_and_counts_laps() {
+        let clock = ManualClock::new(Instant::now());
+        let mut tcv = frames(&clock);
+        assert_eq!(tcv.current(), &'a');
+        clock.advance(secs(2));
+        assert_eq!(tcv.current(), &'c');
+        assert_eq!(tcv.time_remaining(), secs(1));
+        clock.advance(secs(4));
+        assert_eq!(tcv.current(), &'a');
+        assert_eq!(tcv.laps_completed(), 1);
+        clock.advance(secs(6 * 10 + 3));
+        assert_eq!(tcv.current(), &'d');
+        assert_eq!(tcv.laps_completed(), 11);
+
+        tcv.restart();
+        assert_eq!(tcv.current(), &'a');
+        clock.advance(secs(2));
+        assert_eq!(tcv.current(), &'c');
+        assert_eq!(tcv.laps_completed(), 11);
+    }
+
+    #[test]
+    fn clock_going_backwards_does_not_recount_laps() {
+        let start = Instant::now();
+        let clock = ManualClock::new(start);
+        let mut tcv = frames(&clock);
+        let wraps = Arc::new(AtomicU64::new(0));
+        let hook_wraps = Arc::clone(&wraps);
+        tcv.on_wrap(move |_| {
+            hook_wraps.fetch_add(1, Ordering::Relaxed);
+        });
+
+        clock.set(start + secs(13));
+        assert_eq!(tcv.current(), &'a');
+        assert_eq!(tcv.laps_completed(), 2);
+        clock.set(start + secs(2));
+        assert_eq!(tcv.current(), &'c');
+        clock.set(start + secs(13));
+        assert_eq!(tcv.current(), &'a');
+        assert_eq!(tcv.laps_completed(), 2);
+        clock.set(start + secs(15));
+        assert_eq!(tcv.current(), &'d');
+        clock.set(start + secs(18));
+        assert_eq!(tcv.current(), &'a');
+        assert_eq!(tcv.laps_completed(), 3);
+        assert_eq!(wraps.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn reject_invalid() {
+        assert_eq!(
+            TimedCircularVec::<u8>::try_from_iter(vec![]).err(),
+            Some(CircularVecError::Empty)
+        );
+        assert_eq!(
+            TimedCircularVec::try_from_iter(vec![(1, secs(0)), (2, secs(0))]).err(),
+            Some(CircularVecError::ZeroTotalDuration)
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! How a CircularVec's cursor moves from one item to the next.
+
+/// The order in which `CircularVec::next` visits the items.
+///
+/// With four items, the modes give these sequences:
+///
+/// - `Wrap`: 0 1 2 3 0 1 2 3 ...
+/// - `Bounce`: 0 1 2 3 2 1 0 1 2 3 ...
+/// - `BounceRepeatEnds`: 0 1 2 3 3 2 1 0 0 1 2 3 ...
+///
+/// A lap is one full period of the sequence, so the lap count goes up
+/// each time the cursor gets back to the first item moving forwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraversalMode {
+    /// Go from the last item straight back to the first.
+    #[default]
+    Wrap,
+    /// Sweep back and forth, turning around at each end without
+    /// returning the end item twice.
+    Bounce,
+    /// Sweep back and forth, returning each end item twice in a row as
+    /// the direction turns around.
+    BounceRepeatEnds,
+}
+
+/// Which way a CircularVec's cursor is sweeping through the items.
+///
+/// In `TraversalMode::Wrap` this is always `Forward`. In the bounce
+/// modes it says which sweep the item `next` returns belongs to. For
+/// `Bounce`, the last item starts the backward sweep and the first item
+/// starts the forward one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Towards higher indexes.
+    #[default]
+    Forward,
+    /// Towards lower indexes.
+    Backward,
+}
+
+/// Where a CircularVec's cursor is: the index of the item `next` will
+/// return, and which way the cursor is sweeping.
+///
+/// Positions order by index first, so in `TraversalMode::Wrap` they order
+/// just like the plain index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The index of the item `next` will return.
+    pub index: usize,
+    /// The way the cursor is sweeping.
+    pub direction: Direction,
+}
+
+// The cursor is modelled as a phase in `0..period(len)`, the number of
+// `next` calls before the sequence repeats. Each phase maps to exactly
+// one (index, direction) pair, so moving the cursor by any amount is
+// modular arithmetic on the phase.
+impl TraversalMode {
+    pub(crate) fn period(self, len: usize) -> usize {
+        match self {
+            TraversalMode::Wrap => len,
+            TraversalMode::Bounce => (2 * (len - 1)).max(1),
+            TraversalMode::BounceRepeatEnds => 2 * len,
+        }
+    }
+
+    pub(crate) fn position_at(self, phase: usize, len: usize) -> Position {
+        let (index, direction) = match self {
+            TraversalMode::Wrap => (phase, Direction::Forward),
+            TraversalMode::Bounce if phase < len - 1 || len == 1 => (phase, Direction::Forward),
+            TraversalMode::Bounce => (2 * (len - 1) - phase, Direction::Backward),
+            TraversalMode::BounceRepeatEnds if phase < len => (phase, Direction::Forward),
+            TraversalMode::BounceRepeatEnds => (2 * len - 1 - phase, Direction::Backward),
+        };
+        Position { index, direction }
+    }
+
+    /// The phase of the position closest to `position` that this mode can
+    /// reach. Only the direction is ever adjusted, for example a backward
+    /// sweep cannot be at the first item in `Bounce` mode.
+    pub(crate) fn phase_of(self, position: Position, len: usize) -> usize {
+        let Position { index, direction } = position;
+        match (self, direction) {
+            (TraversalMode::Wrap, _) => index,
+            (TraversalMode::Bounce, Direction::Forward) if index < len - 1 => index,
+            (TraversalMode::Bounce, _) if index == 0 => 0,
+            (TraversalMode::Bounce, _) => 2 * (len - 1) - index,
+            (TraversalMode::BounceRepeatEnds, Direction::Forward) => index,
+            (TraversalMode::BounceRepeatEnds, Direction::Backward) => 2 * len - 1 - index,
+        }
+    }
+
+    /// The other phase with the same index as `phase`, on the sweep going
+    /// the opposite way, or `phase` itself if it is the only one.
+    pub(crate) fn mirror(self, phase: usize, len: usize) -> usize {
+        match self {
+            TraversalMode::Wrap => phase,
+            TraversalMode::Bounce => (self.period(len) - phase) % self.period(len),
+            TraversalMode::BounceRepeatEnds => 2 * len - 1 - phase,
+        }
+    }
+}
+
+/// The greatest common divisor of `a` and `b`, with `gcd(0, b) == b`.
+pub(crate) fn gcd(mut a: usize, mut b: usize) -> usize {
+    while a != 0 {
+        let r = b % a;
+        b = a;
+        a = r;
+    }
+    b
+}
+
+/// The inverse of `a` modulo `m`, for `a` coprime with `m`.
+pub(crate) fn mod_inverse(a: usize, m: usize) -> usize {
+    // Extended Euclid. The coefficients stay below `m` in magnitude.
+    let (mut r0, mut r1) = (a as i128, m as i128);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(m as i128) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(mode: TraversalMode, len: usize) -> std::vec::Vec<usize> {
+        (0..mode.period(len))
+            .map(|phase| mode.position_at(phase, len).index)
+            .collect()
+    }
+
+    #[test]
+    fn sequences() {
+        assert_eq!(sequence(TraversalMode::Wrap, 4), [0, 1, 2, 3]);
+        assert_eq!(sequence(TraversalMode::Bounce, 4), [0, 1, 2, 3, 2, 1]);
+        assert_eq!(
+            sequence(TraversalMode::BounceRepeatEnds, 4),
+            [0, 1, 2, 3, 3, 2, 1, 0]
+        );
+        assert_eq!(sequence(TraversalMode::Bounce, 1), [0]);
+        assert_eq!(sequence(TraversalMode::Bounce, 2), [0, 1]);
+        assert_eq!(sequence(TraversalMode::BounceRepeatEnds, 1), [0, 0]);
+    }
+
+    #[test]
+    fn inverses() {
+        assert_eq!(mod_inverse(3, 7), 5);
+        assert_eq!(mod_inverse(1, 1), 0);
+        assert_eq!(mod_inverse(7, 10), 3);
+    }
+
+    #[test]
+    fn phases_round_trip() {
+        for &mode in &[
+            TraversalMode::Wrap,
+            TraversalMode::Bounce,
+            TraversalMode::BounceRepeatEnds,
+        ] {
+            for len in 1..6 {
+                for phase in 0..mode.period(len) {
+                    let position = mode.position_at(phase, len);
+                    assert_eq!(mode.phase_of(position, len), phase, "{:?} {}", mode, len);
+                    let mirror = mode.mirror(phase, len);
+                    assert_eq!(mode.position_at(mirror, len).index, position.index);
+                    assert_eq!(mode.mirror(mirror, len), phase);
+                }
+            }
+        }
+    }
+}
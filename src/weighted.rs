@@ -0,0 +1,196 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ops::Deref;
+
+use crate::CircularVecError;
+
+/// A circular collection where each item has a weight, and `next` returns
+/// items in proportion to their weights.
+///
+/// Selection uses smooth weighted round-robin, the algorithm nginx uses
+/// for upstream servers. Over a period of `total_weight()` calls, each
+/// item is returned exactly as many times as its weight, and those
+/// returns are spread out instead of bunched together: weights of 5, 1
+/// and 1 for `a`, `b` and `c` give `a a b a c a a`. Items with a weight
+/// of zero are never returned.
+///
+///     # use circular_vec::WeightedCircularVec;
+///     let mut wcv = WeightedCircularVec::try_from_iter(vec![("a", 5), ("b", 1), ("c", 1)]).unwrap();
+///     let picks: String = (0..7).map(|_| *wcv.next()).collect();
+///     assert_eq!(picks, "aabacaa");
+///
+/// At least one item must have a non-zero weight, so there is always an
+/// item for `next` to return.
+#[derive(Clone, Debug)]
+pub struct WeightedCircularVec<T> {
+    items: Vec<T>,
+    weights: Vec<u32>,
+    current: Vec<i64>,
+    total: u64,
+}
+
+impl<T> WeightedCircularVec<T> {
+    /// Create a WeightedCircularVec from `(item, weight)` pairs.
+    ///
+    /// Returns `CircularVecError::Empty` if there are no pairs, or
+    /// `CircularVecError::ZeroTotalWeight` if every weight is zero.
+    pub fn try_from_iter<I: IntoIterator<Item = (T, u32)>>(
+        iter: I,
+    ) -> Result<Self, CircularVecError> {
+        let (items, weights): (Vec<T>, Vec<u32>) = iter.into_iter().unzip();
+        if items.is_empty() {
+            return Err(CircularVecError::Empty);
+        }
+        let total = weights.iter().map(|&w| u64::from(w)).sum();
+        if total == 0 {
+            return Err(CircularVecError::ZeroTotalWeight);
+        }
+        Ok(WeightedCircularVec {
+            current: alloc::vec![0; items.len()],
+            items,
+            weights,
+            total,
+        })
+    }
+
+    /// Get an immutable reference to the next item, chosen by smooth
+    /// weighted round-robin.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &T {
+        let mut best: Option<usize> = None;
+        for (i, &weight) in self.weights.iter().enumerate() {
+            if weight == 0 {
+                continue;
+            }
+            self.current[i] += i64::from(weight);
+            if best.is_none_or(|b| self.current[i] > self.current[b]) {
+                best = Some(i);
+            }
+        }
+        // There is always an item with a non-zero weight.
+        let best = best.expect("total weight is never zero");
+        self.current[best] -= self.total as i64;
+        &self.items[best]
+    }
+
+    /// The weight of the item at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn weight(&self, index: usize) -> u32 {
+        self.weights[index]
+    }
+
+    /// Change the weight of the item at `index`. A weight of zero stops
+    /// the item from being returned until its weight is raised again.
+    ///
+    /// Changing a weight restarts the smooth sequence, so the next
+    /// `total_weight()` calls to `next` form a complete period with the
+    /// new weights.
+    ///
+    /// Returns `CircularVecError::ZeroTotalWeight` and leaves the weights
+    /// untouched if this would make every weight zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set_weight(&mut self, index: usize, weight: u32) -> Result<(), CircularVecError> {
+        let total = self.total - u64::from(self.weights[index]) + u64::from(weight);
+        if total == 0 {
+            return Err(CircularVecError::ZeroTotalWeight);
+        }
+        self.weights[index] = weight;
+        self.total = total;
+        self.current.iter_mut().for_each(|c| *c = 0);
+        Ok(())
+    }
+
+    /// The sum of all weights: the length of one full period of `next`.
+    pub fn total_weight(&self) -> u64 {
+        self.total
+    }
+}
+
+impl<T> TryFrom<Vec<(T, u32)>> for WeightedCircularVec<T> {
+    type Error = CircularVecError;
+
+    fn try_from(pairs: Vec<(T, u32)>) -> Result<Self, Self::Error> {
+        Self::try_from_iter(pairs)
+    }
+}
+
+impl<T> Deref for WeightedCircularVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn picks(wcv: &mut WeightedCircularVec<char>, n: usize) -> Vec<char> {
+        (0..n).map(|_| *wcv.next()).collect()
+    }
+
+    #[test]
+    fn smooth_sequence() {
+        let mut wcv =
+            WeightedCircularVec::try_from_iter(vec![('a', 5), ('b', 1), ('c', 1)]).unwrap();
+        assert_eq!(
+            picks(&mut wcv, 14),
+            "aabacaaaabacaa".chars().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn distribution_over_full_period() {
+        let weights = [3, 2, 0, 1, 4];
+        let mut wcv = WeightedCircularVec::try_from_iter(
+            ['a', 'b', 'c', 'd', 'e']
+                .iter()
+                .copied()
+                .zip(weights.iter().copied()),
+        )
+        .unwrap();
+        assert_eq!(wcv.total_weight(), 10);
+
+        for _ in 0..3 {
+            let period = picks(&mut wcv, 10);
+            for (item, &weight) in wcv.iter().zip(weights.iter()) {
+                let count = period.iter().filter(|&p| p == item).count();
+                assert_eq!(count, weight as usize, "item {}", item);
+            }
+        }
+    }
+
+    #[test]
+    fn change_weights() {
+        let mut wcv = WeightedCircularVec::try_from_iter(vec![('a', 1), ('b', 1)]).unwrap();
+        assert_eq!(wcv.set_weight(0, 0), Ok(()));
+        assert_eq!(picks(&mut wcv, 3), ['b', 'b', 'b']);
+        assert_eq!(wcv.set_weight(1, 0), Err(CircularVecError::ZeroTotalWeight));
+        assert_eq!(wcv.weight(1), 1);
+
+        wcv.set_weight(0, 2).unwrap();
+        assert_eq!(picks(&mut wcv, 3), ['a', 'b', 'a']);
+    }
+
+    #[test]
+    fn reject_invalid() {
+        assert_eq!(
+            WeightedCircularVec::<u8>::try_from(vec![]).err().unwrap(),
+            CircularVecError::Empty
+        );
+        assert_eq!(
+            WeightedCircularVec::try_from(vec![(1, 0), (2, 0)])
+                .err()
+                .unwrap(),
+            CircularVecError::ZeroTotalWeight
+        );
+    }
+}
@@ -0,0 +1,31 @@
+//! Index arithmetic shared by the circular structures in this crate.
+//! Every function expects `index < len` and `len > 0`.
+
+pub(crate) fn increment(index: usize, len: usize) -> usize {
+    forward(index, 1, len)
+}
+
+pub(crate) fn decrement(index: usize, len: usize) -> usize {
+    backward(index, 1, len)
+}
+
+/// Move `index` forwards by `n`, wrapping around at `len`.
+pub(crate) fn forward(index: usize, n: usize, len: usize) -> usize {
+    // Written so that `index + n % len` cannot overflow.
+    let n = n % len;
+    if n >= len - index {
+        n - (len - index)
+    } else {
+        index + n
+    }
+}
+
+/// Move `index` backwards by `n`, wrapping around at `len`.
+pub(crate) fn backward(index: usize, n: usize, len: usize) -> usize {
+    let n = n % len;
+    if n > index {
+        len - (n - index)
+    } else {
+        index - n
+    }
+}